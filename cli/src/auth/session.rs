@@ -42,6 +42,7 @@ pub fn clear_session_token() {
 pub trait SessionKeeper {
     fn save(&self, token: &Uuid) -> std::io::Result<()>;
     fn load(&self) -> Option<Uuid>;
+    fn clear(&self);
 }
 
 pub struct FileSessionKeeper;
@@ -65,6 +66,10 @@ impl SessionKeeper for  FileSessionKeeper {
         let data: SessionFile = serde_json::from_slice(&bytes).ok()?;
         Uuid::parse_str(&data.session_token).ok()
     }
+
+    fn clear(&self) {
+        clear_session_token();
+    }
 }
 
 impl<T> SessionKeeper for &T
@@ -78,4 +83,8 @@ where
     fn load(&self) -> Option<uuid::Uuid> {
         (**self).load()
     }
+
+    fn clear(&self) {
+        (**self).clear()
+    }
 }
@@ -1,5 +1,7 @@
 use async_trait::async_trait;
-use uuid::Uuid;
+use std::time::Duration;
+
+use common::protocol::{GameSettings, Request, Response};
 
 use crate::{
     auth::session::SessionKeeper,
@@ -9,14 +11,31 @@ use crate::{
 pub struct CreateCommand<C: Client, S: SessionKeeper> {
     pub client: C,
     session: S,
+    settings: GameSettings,
 }
 
 impl<C: Client, S: SessionKeeper> CreateCommand<C, S> {
-    pub fn new(client: C, session: S) -> Self {
-        Self { client, session }
+    pub fn new(client: C, session: S, settings: GameSettings) -> Self {
+        Self { client, session, settings }
     }
 }
 
+/// Reads a phase length like `24h`, `90m` or `45s`
+pub fn parse_length(input: &str) -> Result<Duration, String> {
+    let input = input.trim();
+    let (amount, unit_secs) = [("h", 60 * 60), ("m", 60), ("s", 1)]
+        .into_iter()
+        .find_map(|(unit, secs)| input.strip_suffix(unit).map(|amount| (amount, secs)))
+        .ok_or_else(|| format!("{input} needs a unit, h, m or s"))?;
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| format!("{input} is not a length, try something like 24h or 90m"))?;
+    amount
+        .checked_mul(unit_secs)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("{input} is far too long"))
+}
+
 #[async_trait]
 impl<C, S> Command for CreateCommand<C, S>
 where
@@ -29,23 +48,35 @@ where
             .load()
             .ok_or(CommandError::NoSessionToken)?;
 
-        // CREATE;<session_id>\n
-        let msg = format!("CREATE;{}\n", session_token);
-
-        self.client.send(&msg).await?;
-        // This does a quick sanity check that the one recieved is the same:
-        let token_str = self.client.read().await?;
+        let request = Request::Create { session: session_token, settings: self.settings.clone() };
+        self.client.send(&request).await?;
 
-        println!("[DEBUG] Recieved from the server: {}", token_str);
+        match self.client.read().await? {
+            Response::Created { game } => {
+                println!("Created game {game}");
+                Ok(())
+            }
+            other => Err(other.into()),
+        }
+    }
+}
 
-        let rec_token =
-            Uuid::parse_str(&token_str).map_err(|_| CommandError::NoSessionToken)?;
-        
-        println!("[DEBUG] Have read from the server: {}", rec_token);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        let expec_token = self.session.load().ok_or(CommandError::NoSessionToken)?;
-        assert_eq!(rec_token, expec_token);
+    #[test]
+    fn lengths_are_read_in_each_unit() {
+        assert_eq!(parse_length("24h"), Ok(Duration::from_secs(24 * 60 * 60)));
+        assert_eq!(parse_length(" 90m "), Ok(Duration::from_secs(90 * 60)));
+        assert_eq!(parse_length("45s"), Ok(Duration::from_secs(45)));
+    }
 
-        Ok(())
+    #[test]
+    fn bad_lengths_are_errors_rather_than_panics() {
+        assert!(parse_length("5é").is_err());
+        assert!(parse_length("90").is_err());
+        assert!(parse_length("h").is_err());
+        assert!(parse_length(&format!("{}h", u64::MAX)).is_err());
     }
 }
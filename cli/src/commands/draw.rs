@@ -0,0 +1,42 @@
+use async_trait::async_trait;
+use common::protocol::{Request, Response};
+
+use crate::{
+    auth::session::SessionKeeper,
+    commands::util::{describe_result, Client, Command, CommandError},
+};
+
+pub struct DrawCommand<C: Client, S: SessionKeeper> {
+    pub client: C,
+    session: S,
+    draw: bool,
+}
+
+impl<C: Client, S: SessionKeeper> DrawCommand<C, S> {
+    pub fn new(client: C, session: S, draw: bool) -> Self {
+        Self { client, session, draw }
+    }
+}
+
+#[async_trait]
+impl<C, S> Command for DrawCommand<C, S>
+where
+    C: Client + Send,
+    S: SessionKeeper + Send,
+{
+    async fn execute(&mut self) -> Result<(), CommandError> {
+        let session_token = self
+            .session
+            .load()
+            .ok_or(CommandError::NoSessionToken)?;
+
+        self.client.send(&Request::Draw { session: session_token, draw: self.draw }).await?;
+        match self.client.read().await? {
+            Response::DrawVoteChanged { draw: true } => println!("You voted for a draw, it happens once every surviving player has"),
+            Response::DrawVoteChanged { draw: false } => println!("You no longer want a draw"),
+            Response::GameFinished { result } => println!("Everyone agreed, the game is over: {}", describe_result(&result)),
+            other => return Err(other.into()),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use common::protocol::{GameFilter, GameSummary, Request, Response};
+use diplomacy::ShortName;
+
+use crate::{
+    auth::session::SessionKeeper,
+    commands::util::{describe_result, Client, Command, CommandError},
+};
+
+pub struct GamesCommand<C: Client, S: SessionKeeper> {
+    pub client: C,
+    session: S,
+    filter: GameFilter,
+}
+
+impl<C: Client, S: SessionKeeper> GamesCommand<C, S> {
+    pub fn new(client: C, session: S, filter: GameFilter) -> Self {
+        Self { client, session, filter }
+    }
+}
+
+/// e.g. `24h`, `90m` or `45s`, whichever unit divides the length exactly
+fn describe_length(length: Duration) -> String {
+    let secs = length.as_secs();
+    if secs.is_multiple_of(3600) {
+        format!("{}h", secs / 3600)
+    } else if secs.is_multiple_of(60) {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{secs}s")
+    }
+}
+
+fn print_game(game: &GameSummary) {
+    let joined = if game.joined { " (joined)" } else { "" };
+    let private = if game.private { " [private]" } else { "" };
+    println!("{}{joined}{private}", game.name);
+    if game.name != game.id.to_string() {
+        println!("  Id: {}", game.id);
+    }
+    match &game.result {
+        Some(result) => println!("  Finished in {}: {}", game.time.short_name(), describe_result(result)),
+        None if !game.started => println!(
+            "  {}/{} players, waiting to start, can start with {}",
+            game.players,
+            game.seats,
+            game.min_players,
+        ),
+        None => println!(
+            "  {}/{} players, {} open seats, now in {}",
+            game.players,
+            game.seats,
+            game.open_seats,
+            game.time.short_name(),
+        ),
+    }
+    println!(
+        "  Variant: {:?}, press: {:?}, deadlines: {} movement, {} retreat, {} build",
+        game.variant,
+        game.press,
+        describe_length(game.deadlines.movement),
+        describe_length(game.deadlines.retreat),
+        describe_length(game.deadlines.build),
+    );
+    if game.spectators > 0 {
+        println!("  Spectators: {}", game.spectators);
+    }
+}
+
+#[async_trait]
+impl<C, S> Command for GamesCommand<C, S>
+where
+    C: Client + Send,
+    S: SessionKeeper + Send,
+{
+    async fn execute(&mut self) -> Result<(), CommandError> {
+        let session_token = self
+            .session
+            .load()
+            .ok_or(CommandError::NoSessionToken)?;
+
+        self.client.send(&Request::ListGames { session: session_token, filter: self.filter }).await?;
+        match self.client.read().await? {
+            Response::Games { games } if games.is_empty() => println!("No games found"),
+            Response::Games { games } => games.iter().for_each(print_game),
+            other => return Err(other.into()),
+        }
+        Ok(())
+    }
+}
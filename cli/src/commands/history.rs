@@ -0,0 +1,71 @@
+use async_trait::async_trait;
+use common::protocol::{PhaseHistory, Request, Response};
+use diplomacy::{ShortName, Time};
+
+use crate::{
+    auth::session::SessionKeeper,
+    commands::util::{Client, Command, CommandError},
+};
+
+pub struct HistoryCommand<C: Client, S: SessionKeeper> {
+    pub client: C,
+    session: S,
+    phase: String,
+}
+
+impl<C: Client, S: SessionKeeper> HistoryCommand<C, S> {
+    pub fn new(client: C, session: S, phase: String) -> Self {
+        Self { client, session, phase }
+    }
+}
+
+fn print_history(history: &PhaseHistory) {
+    println!("Orders for {}", history.phase.short_name());
+    if history.orders.is_empty() {
+        println!("  No orders were given");
+    }
+    for nation_orders in &history.orders {
+        println!("{}:", nation_orders.nation);
+        for order in &nation_orders.orders {
+            println!("  {order}");
+        }
+    }
+
+    if history.results.is_empty() {
+        println!("The phase has not been resolved");
+        return;
+    }
+    println!("Results:");
+    for result in &history.results {
+        let mark = if result.succeeded { "ok" } else { "failed" };
+        println!("  [{mark}] {} ({})", result.order, result.outcome);
+    }
+}
+
+#[async_trait]
+impl<C, S> Command for HistoryCommand<C, S>
+where
+    C: Client + Send,
+    S: SessionKeeper + Send,
+{
+    async fn execute(&mut self) -> Result<(), CommandError> {
+        let session_token = self
+            .session
+            .load()
+            .ok_or(CommandError::NoSessionToken)?;
+        let phase: Time = self
+            .phase
+            .to_uppercase()
+            .parse()
+            .map_err(|_| CommandError::InvalidPhase)?;
+
+        self.client.send(&Request::History { session: session_token, phase }).await?;
+        match self.client.read().await? {
+            Response::History(history) => {
+                print_history(&history);
+                Ok(())
+            }
+            other => Err(other.into()),
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use async_trait::async_trait;
+use common::protocol::{Request, Response};
 use uuid::Uuid;
 
 use crate::{
@@ -10,14 +11,16 @@ pub struct JoinCommand<C: Client, S: SessionKeeper> {
     pub client: C,
     session: S,
     game: String,
+    password: Option<String>,
 }
 
 impl<C: Client, S: SessionKeeper> JoinCommand<C, S> {
-    pub fn new(client: C, session: S, game: String) -> Self {
+    pub fn new(client: C, session: S, game: String, password: Option<String>) -> Self {
         Self {
             client,
             session,
             game,
+            password,
         }
     }
 }
@@ -34,18 +37,17 @@ where
             .load()
             .ok_or(CommandError::NoSessionToken)?;
 
-        // JOIN;<session_id>;<game_id>\n
-        let msg = format!("JOIN;{};{}\n", session_token, self.game);
+        let game = Uuid::parse_str(&self.game).map_err(|_| CommandError::InvalidGameId)?;
 
-        self.client.send(&msg).await?;
-        // This does a quick sanity check that the one recieved is the same:
-        let token_str = self.client.read().await?;
-        let rec_token =
-            Uuid::parse_str(&token_str).map_err(|_| CommandError::NoSessionToken)?;
+        let request = Request::Join { session: session_token, game, password: self.password.clone() };
+        self.client.send(&request).await?;
 
-        let expec_token = self.session.load().ok_or(CommandError::NoSessionToken)?;
-        assert_eq!(rec_token, expec_token);
-
-        Ok(())
+        match self.client.read().await? {
+            Response::Joined { game } => {
+                println!("Joined game {game}");
+                Ok(())
+            }
+            other => Err(other.into()),
+        }
     }
 }
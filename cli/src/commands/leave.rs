@@ -0,0 +1,52 @@
+use async_trait::async_trait;
+use common::protocol::{Request, Response};
+use uuid::Uuid;
+
+use crate::{
+    auth::session::SessionKeeper,
+    commands::util::{Client, Command, CommandError},
+};
+
+pub struct LeaveCommand<C: Client, S: SessionKeeper> {
+    pub client: C,
+    session: S,
+    /// Defaults to the current game
+    game: Option<String>,
+}
+
+impl<C: Client, S: SessionKeeper> LeaveCommand<C, S> {
+    pub fn new(client: C, session: S, game: Option<String>) -> Self {
+        Self { client, session, game }
+    }
+}
+
+#[async_trait]
+impl<C, S> Command for LeaveCommand<C, S>
+where
+    C: Client + Send,
+    S: SessionKeeper + Send,
+{
+    async fn execute(&mut self) -> Result<(), CommandError> {
+        let session_token = self
+            .session
+            .load()
+            .ok_or(CommandError::NoSessionToken)?;
+
+        let game = self
+            .game
+            .as_deref()
+            .map(Uuid::parse_str)
+            .transpose()
+            .map_err(|_| CommandError::InvalidGameId)?;
+
+        self.client.send(&Request::Leave { session: session_token, game }).await?;
+        match self.client.read().await? {
+            Response::Left { game, current: Some(current) } => {
+                println!("Left game {game}, your current game is now {current}")
+            }
+            Response::Left { game, current: None } => println!("Left game {game}"),
+            other => return Err(other.into()),
+        }
+        Ok(())
+    }
+}
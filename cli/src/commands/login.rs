@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use uuid::Uuid;
+use common::protocol::{Request, Response};
 
 use crate::{
     auth::session::SessionKeeper,
@@ -31,18 +31,21 @@ where
     S: SessionKeeper + Send,
 {
     async fn execute(&mut self) -> Result<(), CommandError> {
-        // LOGIN;<username>;<password>\n
-        let msg = format!("LOGIN;{};{}\n", self.username, self.password);
+        let request = Request::Login {
+            username: self.username.clone(),
+            password: self.password.clone(),
+        };
+        self.client.send(&request).await?;
 
-        self.client.send(&msg).await?;
-
-        let token_str = self.client.read().await?;
-        let session_token =
-            Uuid::parse_str(&token_str).map_err(|_| CommandError::NoSessionToken)?;
+        let (session_token, user) = match self.client.read().await? {
+            Response::Session { token, user } => (token, user),
+            other => return Err(other.into()),
+        };
 
         self.session
             .save(&session_token)
             .map_err(|_| CommandError::SessionSaveFailed)?;
+        println!("Logged in as {}", user.username);
 
         Ok(())
     }
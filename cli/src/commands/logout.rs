@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+use common::protocol::{ErrorCode, Request, Response};
+
+use crate::{
+    auth::session::SessionKeeper,
+    commands::util::{Client, Command, CommandError},
+};
+
+pub struct LogoutCommand<C: Client, S: SessionKeeper> {
+    pub client: C,
+    session: S,
+}
+
+impl<C: Client, S: SessionKeeper> LogoutCommand<C, S> {
+    pub fn new(client: C, session: S) -> Self {
+        Self { client, session }
+    }
+}
+
+#[async_trait]
+impl<C, S> Command for LogoutCommand<C, S>
+where
+    C: Client + Send,
+    S: SessionKeeper + Send,
+{
+    async fn execute(&mut self) -> Result<(), CommandError> {
+        let session_token = self
+            .session
+            .load()
+            .ok_or(CommandError::NoSessionToken)?;
+
+        self.client.send(&Request::Logout { session: session_token }).await?;
+        let response = self.client.read().await?;
+
+        // The token is no good to us whatever the server said, so always forget it
+        self.session.clear();
+        match response {
+            Response::LoggedOut => {}
+            Response::Error(error)
+                if matches!(error.code, ErrorCode::SessionUnknown | ErrorCode::SessionExpired) => {}
+            other => return Err(other.into()),
+        }
+
+        println!("Logged out");
+        Ok(())
+    }
+}
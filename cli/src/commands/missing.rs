@@ -0,0 +1,49 @@
+use async_trait::async_trait;
+use common::protocol::{Request, Response};
+use diplomacy::ShortName;
+
+use crate::{
+    auth::session::SessionKeeper,
+    commands::util::{Client, Command, CommandError},
+};
+
+pub struct MissingCommand<C: Client, S: SessionKeeper> {
+    pub client: C,
+    session: S,
+}
+
+impl<C: Client, S: SessionKeeper> MissingCommand<C, S> {
+    pub fn new(client: C, session: S) -> Self {
+        Self { client, session }
+    }
+}
+
+#[async_trait]
+impl<C, S> Command for MissingCommand<C, S>
+where
+    C: Client + Send,
+    S: SessionKeeper + Send,
+{
+    async fn execute(&mut self) -> Result<(), CommandError> {
+        let session_token = self
+            .session
+            .load()
+            .ok_or(CommandError::NoSessionToken)?;
+
+        self.client.send(&Request::MissingOrders { session: session_token }).await?;
+        let units = match self.client.read().await? {
+            Response::MissingOrders { units } => units,
+            other => return Err(other.into()),
+        };
+
+        if units.is_empty() {
+            println!("All of your units have orders");
+            return Ok(());
+        }
+        println!("These units have no orders yet:");
+        for (unit_type, region) in units {
+            println!("  {} {}", unit_type.short_name(), region.short_name());
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,9 @@
 use async_trait::async_trait;
 use common::context::GameContext;
-use diplomacy::judge::MappedMainOrder;
+use common::protocol::{OrderSet, Request, Response};
+use std::str::FromStr;
+
+use diplomacy::{Phase, Time};
 use uuid::Uuid;
 
 use crate::auth::session::SessionKeeper;
@@ -12,6 +15,8 @@ pub struct OrderCommand<C: Client, S: SessionKeeper> {
     client: C,
     session: S,
     orders: Option<String>, // shortcut flag (still unused)
+    /// One of the player's other games, instead of the current one
+    game: Option<String>,
 }
 
 impl<C: Client, S: SessionKeeper> OrderCommand<C, S> {
@@ -19,45 +24,38 @@ impl<C: Client, S: SessionKeeper> OrderCommand<C, S> {
         client: C,
         session: S,
         orders: Option<String>,
+        game: Option<String>,
     ) -> Self {
         Self {
             client,
             session,
-            orders
+            orders,
+            game,
         }
     }
 
-    /// Shortcut flags hook (intentionally no-op for now)
-    fn parse_flags(&self) -> Result<Vec<MappedMainOrder>, CommandError> {
-        if let Some(orders) = &self.orders {
-            println!("[DEBUG] Shortcut flags detected ");
-            // Try to parse as Vec<String> first
-            let order_strings: Vec<String> = serde_json::from_str(orders)
-                .map_err(|e| {
-                    println!("[DEBUG] Failed to parse as string array: {}", e);
-                    CommandError::CannotParseOrder(e)
-                })?;
-
-            // Parse each string into MappedMainOrder
-            let parsed_orders: Result<Vec<MappedMainOrder>, _> = order_strings
-                .iter()
-                .map(|s| s.parse::<MappedMainOrder>().map_err(|_| CommandError::WriteFailure))
-                .collect();
-            
-            return parsed_orders;
-        } else {
-            Err(CommandError::FlagNotFound)
-        }
+    /// Reads the `--orders` flag as orders for `phase`
+    fn parse_flags(&self, phase: Phase) -> Result<OrderSet, CommandError> {
+        let Some(orders) = &self.orders else {
+            return Err(CommandError::FlagNotFound);
+        };
+        println!("[DEBUG] Shortcut flags detected ");
+        let order_strings: Vec<String> = serde_json::from_str(orders)
+            .map_err(|e| {
+                println!("[DEBUG] Failed to parse as string array: {}", e);
+                CommandError::CannotParseOrder(e)
+            })?;
+
+        parse_orders(phase, &order_strings)
     }
 
-    async fn get_context(&mut self, session_token: Uuid) -> Result<GameContext, CommandError> {
-        let msg = format!("CONTEXT;{}\n", session_token);
-        self.client.send(&msg).await?;
-        let context_rec = self.client.read().await?;
-        let context: GameContext = serde_json::from_str(&context_rec)
-            .or(Err(CommandError::NoContextFound))?;
-        return Ok(context);
-    } 
+    async fn get_context(&mut self, session_token: Uuid, game: Option<Uuid>) -> Result<GameContext, CommandError> {
+        self.client.send(&Request::Context { session: session_token, game }).await?;
+        match self.client.read().await? {
+            Response::Context(context) => Ok(*context),
+            other => Err(other.into()),
+        }
+    }
 }
 
 #[async_trait]
@@ -72,16 +70,28 @@ where
             .load()
             .ok_or(CommandError::NoSessionToken)?;
 
+        let game = self
+            .game
+            .as_deref()
+            .map(Uuid::parse_str)
+            .transpose()
+            .map_err(|_| CommandError::InvalidGameId)?;
+        let context = self
+            .get_context(session_token, game)
+            .await?;
+        if context.result.is_some() {
+            return Err(CommandError::GameOver);
+        }
+        if context.user_nation.is_none() {
+            return Err(CommandError::Spectating);
+        }
+        let phase = context.time.as_ref().map_or(Phase::Main, Time::phase);
+
         // TODO: I think result isn't the best return var choice
-        let main_orders: Vec<MappedMainOrder> = match self.parse_flags() {
+        let orders = match self.parse_flags(phase) {
             Ok(orders) => {orders}
-            Err(_) => {
-                // Falling back to interactive mode
-                let context = self
-                .get_context(session_token)
-                .await?;
-
-                // CONTEXT;<session_token>\n
+            // Falling back to interactive mode
+            Err(CommandError::FlagNotFound) if phase == Phase::Main => {
                 let mut machine = StateMachine::new(
                     UiState::ShowUnit(ShowUnitState),
                     context
@@ -93,25 +103,107 @@ where
                     let input = String::new();
                     machine.update(input.trim());
                 }
-                machine.data.orders
+                OrderSet::Main(machine.data.orders)
             }
+            // The step by step menus only know movement orders, retreats and builds are typed in
+            Err(CommandError::FlagNotFound) => parse_orders(phase, &prompt_orders(phase))?,
+            Err(e) => return Err(e),
         };
 
-        // Orders found, sending over as Json
-        let orders_json =
-            serde_json::to_string(&main_orders)
-                .map_err(|_| CommandError::WriteFailure)?;
+        let request = Request::Order {
+            session: session_token,
+            orders,
+            game,
+        };
+        self.client.send(&request).await?;
 
-        // ORDER;MAIN;<session_id>;<orders>\n
-        let msg = format!(
-            "ORDER;MAIN;{};{}\n",
-            session_token,
-            orders_json
-        );
+        match self.client.read().await? {
+            Response::OrdersAccepted => println!("Orders saved, run `ready` once you are happy with them"),
+            Response::PhaseAdvanced => println!("Orders accepted, the phase has been resolved"),
+            other => return Err(other.into()),
+        }
+        Ok(())
+    }
+}
 
-        println!("{}", msg);
+/// Turns order strings into the set for `phase`. Retreating units can be told to `disband`
+/// and `waive` gives up a build
+fn parse_orders(phase: Phase, order_strings: &[String]) -> Result<OrderSet, CommandError> {
+    let orders = match phase {
+        Phase::Main => OrderSet::Main(parse_all(order_strings.iter().map(String::as_str))?),
+        // The judge calls a disband a hold during retreats
+        Phase::Retreat => {
+            let orders: Vec<String> = order_strings
+                .iter()
+                .map(|s| match s.trim().strip_suffix("disband") {
+                    Some(unit) => format!("{unit}hold"),
+                    None => s.clone(),
+                })
+                .collect();
+            OrderSet::Retreat(parse_all(orders.iter().map(String::as_str))?)
+        }
+        Phase::Build => OrderSet::Build(parse_all(
+            order_strings
+                .iter()
+                .map(String::as_str)
+                .filter(|s| !s.trim().eq_ignore_ascii_case("waive")),
+        )?),
+    };
+    Ok(orders)
+}
 
-        self.client.send(&msg).await?;
-        Ok(())
+/// Asks for one order at a time until an empty line, for the phases without menus
+fn prompt_orders(phase: Phase) -> Vec<String> {
+    let example = match phase {
+        Phase::Retreat => "e.g. ENG: F lon -> wal or ENG: F lon disband",
+        _ => "e.g. ENG: A lvp build, ENG: F lon disband or waive",
+    };
+    let mut orders = Vec::new();
+    while let Ok(order) = inquire::Text::new("Order:").with_help_message(example).prompt() {
+        if order.trim().is_empty() {
+            break;
+        }
+        orders.push(order);
+    }
+    orders
+}
+
+/// Parses every order, a single bad one fails them all
+fn parse_all<'a, O: FromStr>(orders: impl Iterator<Item = &'a str>) -> Result<Vec<O>, CommandError> {
+    orders
+        .map(|s| {
+            // The diplomacy parser indexes nation, unit, region and command without checking
+            if s.split_whitespace().count() < 4 {
+                return Err(CommandError::WriteFailure);
+            }
+            s.parse::<O>().map_err(|_| CommandError::WriteFailure)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn orders(orders: &[&str]) -> Vec<String> {
+        orders.iter().map(|o| o.to_string()).collect()
+    }
+
+    #[test]
+    fn each_phase_reads_its_own_orders() {
+        let main = parse_orders(Phase::Main, &orders(&["GER: A ber -> sil", "GER: F kie holds"]));
+        assert!(matches!(main, Ok(OrderSet::Main(o)) if o.len() == 2));
+        let retreat = parse_orders(Phase::Retreat, &orders(&["GER: A mun disband"]));
+        assert!(matches!(retreat, Ok(OrderSet::Retreat(o)) if o.len() == 1));
+        let build = parse_orders(Phase::Build, &orders(&["GER: A ber build", "waive"]));
+        assert!(matches!(build, Ok(OrderSet::Build(o)) if o.len() == 1));
+    }
+
+    #[test]
+    fn short_orders_are_errors_rather_than_panics() {
+        assert!(parse_orders(Phase::Main, &orders(&["waive"])).is_err());
+        assert!(parse_orders(Phase::Main, &orders(&["GER: A ber"])).is_err());
+        assert!(parse_orders(Phase::Retreat, &orders(&["disband"])).is_err());
+        assert!(parse_orders(Phase::Build, &orders(&["GER: build"])).is_err());
     }
 }
@@ -0,0 +1,42 @@
+use async_trait::async_trait;
+use common::protocol::{Request, Response};
+use diplomacy::Nation;
+
+use crate::{
+    auth::session::SessionKeeper,
+    commands::util::{Client, Command, CommandError},
+};
+
+pub struct PreferCommand<C: Client, S: SessionKeeper> {
+    pub client: C,
+    session: S,
+    nations: Vec<String>,
+}
+
+impl<C: Client, S: SessionKeeper> PreferCommand<C, S> {
+    pub fn new(client: C, session: S, nations: Vec<String>) -> Self {
+        Self { client, session, nations }
+    }
+}
+
+#[async_trait]
+impl<C, S> Command for PreferCommand<C, S>
+where
+    C: Client + Send,
+    S: SessionKeeper + Send,
+{
+    async fn execute(&mut self) -> Result<(), CommandError> {
+        let session_token = self
+            .session
+            .load()
+            .ok_or(CommandError::NoSessionToken)?;
+
+        let nations: Vec<Nation> = self.nations.iter().map(|name| Nation::from(name.to_uppercase().as_str())).collect();
+        self.client.send(&Request::Preferences { session: session_token, nations }).await?;
+        match self.client.read().await? {
+            Response::PreferencesSaved => println!("Saved, nations are handed out when the game starts"),
+            other => return Err(other.into()),
+        }
+        Ok(())
+    }
+}
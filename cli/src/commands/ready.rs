@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+use common::protocol::{Request, Response};
+
+use crate::{
+    auth::session::SessionKeeper,
+    commands::util::{describe_result, Client, Command, CommandError},
+};
+
+pub struct ReadyCommand<C: Client, S: SessionKeeper> {
+    pub client: C,
+    session: S,
+    ready: bool,
+}
+
+impl<C: Client, S: SessionKeeper> ReadyCommand<C, S> {
+    pub fn new(client: C, session: S, ready: bool) -> Self {
+        Self { client, session, ready }
+    }
+}
+
+#[async_trait]
+impl<C, S> Command for ReadyCommand<C, S>
+where
+    C: Client + Send,
+    S: SessionKeeper + Send,
+{
+    async fn execute(&mut self) -> Result<(), CommandError> {
+        let session_token = self
+            .session
+            .load()
+            .ok_or(CommandError::NoSessionToken)?;
+
+        self.client.send(&Request::Ready { session: session_token, ready: self.ready }).await?;
+        match self.client.read().await? {
+            Response::ReadyChanged { ready: true } => println!("You are ready, the phase resolves once everyone is"),
+            Response::ReadyChanged { ready: false } => println!("You are no longer ready, your orders can still be changed"),
+            Response::PhaseAdvanced => println!("Everyone is ready, the phase has been resolved"),
+            Response::GameFinished { result } => println!("Everyone is ready, the game is over: {}", describe_result(&result)),
+            other => return Err(other.into()),
+        }
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
 use crate::auth::session::SessionKeeper;
 use crate::commands::util::{Client, Command, CommandError};
 use async_trait::async_trait;
-use uuid::Uuid;
+use common::protocol::{Request, Response};
 
 pub struct RegisterCommand<C: Client, S: SessionKeeper> {
     client: C,
@@ -28,14 +28,16 @@ where
     S: SessionKeeper + Send,
 {
     async fn execute(&mut self) -> Result<(), CommandError> {
-        // REGISTER;<username>;<password>\n
-        let msg = format!("REGISTER;{};{}\n", self.username, self.password);
+        let request = Request::Register {
+            username: self.username.clone(),
+            password: self.password.clone(),
+        };
+        self.client.send(&request).await?;
 
-        self.client.send(&msg).await?;
-
-        let token_str = self.client.read().await?;
-        let session_token =
-            Uuid::parse_str(&token_str).map_err(|_| CommandError::NoSessionToken)?;
+        let session_token = match self.client.read().await? {
+            Response::Session { token, .. } => token,
+            other => return Err(other.into()),
+        };
 
         self.session
             .save(&session_token)
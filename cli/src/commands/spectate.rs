@@ -0,0 +1,53 @@
+use async_trait::async_trait;
+use common::protocol::{Request, Response};
+use uuid::Uuid;
+
+use crate::{
+    auth::session::SessionKeeper,
+    commands::util::{Client, Command, CommandError},
+};
+
+pub struct SpectateCommand<C: Client, S: SessionKeeper> {
+    pub client: C,
+    session: S,
+    game: String,
+    password: Option<String>,
+}
+
+impl<C: Client, S: SessionKeeper> SpectateCommand<C, S> {
+    pub fn new(client: C, session: S, game: String, password: Option<String>) -> Self {
+        Self {
+            client,
+            session,
+            game,
+            password,
+        }
+    }
+}
+
+#[async_trait]
+impl<C, S> Command for SpectateCommand<C, S>
+where
+    C: Client + Send,
+    S: SessionKeeper + Send,
+{
+    async fn execute(&mut self) -> Result<(), CommandError> {
+        let session_token = self
+            .session
+            .load()
+            .ok_or(CommandError::NoSessionToken)?;
+
+        let game = Uuid::parse_str(&self.game).map_err(|_| CommandError::InvalidGameId)?;
+
+        let request = Request::Spectate { session: session_token, game, password: self.password.clone() };
+        self.client.send(&request).await?;
+
+        match self.client.read().await? {
+            Response::Spectating { game } => {
+                println!("Spectating game {game}, follow it with status, watch and history");
+                Ok(())
+            }
+            other => Err(other.into()),
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use async_trait::async_trait;
+use common::protocol::{Request, Response};
+
+use crate::{
+    auth::session::SessionKeeper,
+    commands::util::{Client, Command, CommandError},
+};
+
+pub struct StartCommand<C: Client, S: SessionKeeper> {
+    pub client: C,
+    session: S,
+}
+
+impl<C: Client, S: SessionKeeper> StartCommand<C, S> {
+    pub fn new(client: C, session: S) -> Self {
+        Self { client, session }
+    }
+}
+
+#[async_trait]
+impl<C, S> Command for StartCommand<C, S>
+where
+    C: Client + Send,
+    S: SessionKeeper + Send,
+{
+    async fn execute(&mut self) -> Result<(), CommandError> {
+        let session_token = self
+            .session
+            .load()
+            .ok_or(CommandError::NoSessionToken)?;
+
+        self.client.send(&Request::Start { session: session_token }).await?;
+        match self.client.read().await? {
+            Response::Started { game } => println!("Game {game} has started, run status to see your nation"),
+            other => return Err(other.into()),
+        }
+        Ok(())
+    }
+}
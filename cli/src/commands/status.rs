@@ -0,0 +1,98 @@
+use async_trait::async_trait;
+use common::context::GameContext;
+use common::protocol::{Request, Response};
+use diplomacy::ShortName;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{
+    auth::session::SessionKeeper,
+    commands::util::{describe_result, Client, Command, CommandError},
+};
+
+pub struct StatusCommand<C: Client, S: SessionKeeper> {
+    pub client: C,
+    session: S,
+    /// One of the player's other games, instead of the current one
+    game: Option<String>,
+}
+
+impl<C: Client, S: SessionKeeper> StatusCommand<C, S> {
+    pub fn new(client: C, session: S, game: Option<String>) -> Self {
+        Self { client, session, game }
+    }
+}
+
+/// e.g. `2026-10-18 14:05 UTC (in 3h 20m)`
+fn describe_deadline(deadline: OffsetDateTime, now: OffsetDateTime) -> String {
+    let left = deadline - now;
+    let when = format!(
+        "{}-{:02}-{:02} {:02}:{:02} UTC",
+        deadline.year(),
+        u8::from(deadline.month()),
+        deadline.day(),
+        deadline.hour(),
+        deadline.minute(),
+    );
+    if left.is_negative() {
+        format!("{when} (passed, adjudicating shortly)")
+    } else {
+        format!("{when} (in {}h {}m)", left.whole_hours(), left.whole_minutes() % 60)
+    }
+}
+
+fn print_status(context: &GameContext) {
+    match &context.user_nation {
+        Some(nation) => println!("You are playing {nation}"),
+        None => println!("You are spectating"),
+    }
+    if let Some(result) = &context.result {
+        println!("The game is over: {}", describe_result(result));
+    } else if let Some(time) = &context.time {
+        println!("Phase: {}", time.short_name());
+    }
+    match context.deadline {
+        Some(deadline) => println!("Deadline: {}", describe_deadline(deadline, OffsetDateTime::now_utc())),
+        None if context.result.is_some() => {}
+        None => println!("Deadline: none until enough players have joined"),
+    }
+    if !context.civil_disorder.is_empty() {
+        let mut nations: Vec<_> = context.civil_disorder.iter().map(|nation| nation.to_string()).collect();
+        nations.sort();
+        println!("In civil disorder: {}", nations.join(", "));
+    }
+
+    let mut centres: Vec<_> = context.supply_centres.iter().collect();
+    centres.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.to_string().cmp(&b.0.to_string())));
+    println!("Supply centres:");
+    for (nation, count) in centres {
+        println!("  {nation}: {count}");
+    }
+}
+
+#[async_trait]
+impl<C, S> Command for StatusCommand<C, S>
+where
+    C: Client + Send,
+    S: SessionKeeper + Send,
+{
+    async fn execute(&mut self) -> Result<(), CommandError> {
+        let session_token = self
+            .session
+            .load()
+            .ok_or(CommandError::NoSessionToken)?;
+
+        let game = self
+            .game
+            .as_deref()
+            .map(Uuid::parse_str)
+            .transpose()
+            .map_err(|_| CommandError::InvalidGameId)?;
+        self.client.send(&Request::Context { session: session_token, game }).await?;
+        match self.client.read().await? {
+            Response::Context(context) => print_status(&context),
+            other => return Err(other.into()),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+use common::protocol::{Request, Response};
+use uuid::Uuid;
+
+use crate::{
+    auth::session::SessionKeeper,
+    commands::util::{Client, Command, CommandError},
+};
+
+pub struct SwitchCommand<C: Client, S: SessionKeeper> {
+    pub client: C,
+    session: S,
+    game: String,
+}
+
+impl<C: Client, S: SessionKeeper> SwitchCommand<C, S> {
+    pub fn new(client: C, session: S, game: String) -> Self {
+        Self { client, session, game }
+    }
+}
+
+#[async_trait]
+impl<C, S> Command for SwitchCommand<C, S>
+where
+    C: Client + Send,
+    S: SessionKeeper + Send,
+{
+    async fn execute(&mut self) -> Result<(), CommandError> {
+        let session_token = self
+            .session
+            .load()
+            .ok_or(CommandError::NoSessionToken)?;
+
+        let game = Uuid::parse_str(&self.game).map_err(|_| CommandError::InvalidGameId)?;
+
+        self.client.send(&Request::Switch { session: session_token, game }).await?;
+        match self.client.read().await? {
+            Response::Switched { game } => println!("Game {game} is now your current game"),
+            other => return Err(other.into()),
+        }
+        Ok(())
+    }
+}
@@ -1,25 +1,107 @@
 use async_trait::async_trait;
+use std::fmt;
+
+use common::protocol::{read_frame, write_frame, ErrorCode, FrameError, ErrorResponse, GameResult, Request, Response};
 use mockall::automock;
 use tokio::net::TcpStream;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 #[derive(Debug)]
 pub enum CommandError {
     ConnectionFailure,
     NoSessionToken,
     WriteFailure,
-    NoSessionTokenRead,
+    /// The response could not be read, or was not a message the CLI understands
+    ReadFailure(FrameError),
     SessionSaveFailed,
     NoContextFound,
     CannotParseOrder(serde_json::Error),
-    FlagNotFound
+    FlagNotFound,
+    InvalidGameId,
+    InvalidPhase,
+    GameOver,
+    Spectating,
+    Server(ErrorResponse),
+    UnexpectedResponse,
+}
+
+impl From<Response> for CommandError {
+    /// Any response a command did not expect is either an error from the server
+    /// or a protocol mismatch
+    fn from(response: Response) -> Self {
+        match response {
+            Response::Error(error) => CommandError::Server(error),
+            _ => CommandError::UnexpectedResponse,
+        }
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::ConnectionFailure => write!(f, "Could not reach the server"),
+            CommandError::NoSessionToken => write!(f, "You are not logged in"),
+            CommandError::WriteFailure => write!(f, "Could not send the request to the server"),
+            CommandError::ReadFailure(e) => write!(f, "Could not read the server's response: {e}"),
+            CommandError::SessionSaveFailed => write!(f, "Could not save your session"),
+            CommandError::NoContextFound => write!(f, "Could not get the state of your game"),
+            CommandError::CannotParseOrder(e) => write!(f, "Could not parse the orders: {e}"),
+            CommandError::FlagNotFound => write!(f, "No orders were given"),
+            CommandError::InvalidGameId => write!(f, "That is not a valid game id"),
+            CommandError::InvalidPhase => write!(f, "That is not a valid phase, try something like S1901M"),
+            CommandError::GameOver => write!(f, "That game is over"),
+            CommandError::Spectating => write!(f, "You are spectating that game, join it to play"),
+            CommandError::Server(error) => write!(f, "{}", describe(error)),
+            CommandError::UnexpectedResponse => write!(f, "The server sent an unexpected response"),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+/// Turns a server error into something a player can act on, falling back to the
+/// server's own message for anything without a better explanation
+fn describe(error: &ErrorResponse) -> String {
+    match error.code {
+        ErrorCode::InvalidCredentials => "Wrong username or password".to_string(),
+        ErrorCode::SessionUnknown => "Your session is unknown or has expired, please log in again".to_string(),
+        ErrorCode::SessionExpired => "Your session has expired, please log in again".to_string(),
+        ErrorCode::NotInGame => "You are not in a game, join or create one first".to_string(),
+        ErrorCode::GameNotFound => "No game exists with that id".to_string(),
+        ErrorCode::GameFull => "That game is full".to_string(),
+        ErrorCode::AlreadyJoined => "You have already joined that game".to_string(),
+        ErrorCode::GameFinished => "That game is over".to_string(),
+        ErrorCode::WrongPassword => "That game is private, join it with the right --password".to_string(),
+        ErrorCode::GameStarted => "That game has already started".to_string(),
+        ErrorCode::GameNotStarted => "The game has not started yet, you get a nation once it does".to_string(),
+        ErrorCode::NotHost => "Only the player who created the game can start it early".to_string(),
+        ErrorCode::WrongPhase => "Those orders are not for the current phase".to_string(),
+        ErrorCode::OrderCountMismatch { expected, found } => {
+            format!("You gave {found} orders but {expected} were expected")
+        }
+        _ => error.message.clone(),
+    }
+}
+
+/// e.g. `FRA won with a solo victory` or `Drawn between ENG, FRA and GER`
+pub fn describe_result(result: &GameResult) -> String {
+    match result {
+        GameResult::Victory { winner } => format!("{winner} won with a solo victory"),
+        GameResult::Draw { nations } => {
+            let mut names: Vec<String> = nations.iter().map(|nation| nation.to_string()).collect();
+            match names.pop() {
+                Some(last) if !names.is_empty() => format!("Drawn between {} and {last}", names.join(", ")),
+                Some(last) => format!("Drawn by {last}"),
+                None => "Drawn".to_string(),
+            }
+        }
+    }
 }
 
 #[automock]
 #[async_trait]
 pub trait Client: Send {
-    async fn send(&mut self, msg: &str) -> Result<(), CommandError>;
-    async fn read(&mut self) -> Result<String, CommandError>;
+    async fn send(&mut self, request: &Request) -> Result<(), CommandError>;
+    async fn read(&mut self) -> Result<Response, CommandError>;
 }
 
 #[async_trait]
@@ -42,21 +124,17 @@ impl TcpClient {
 
 #[async_trait]
 impl Client for TcpClient {
-    async fn send(&mut self, msg: &str) -> Result<(), CommandError> {
-        self.stream
-            .write_all(msg.as_bytes())
+    async fn send(&mut self, request: &Request) -> Result<(), CommandError> {
+        write_frame(&mut self.stream, request)
             .await
             .map_err(|_| CommandError::WriteFailure)
     }
 
-    async fn read(&mut self) -> Result<String, CommandError> {
-        let mut buf = vec![0u8; 1024];
-        let n: usize = self.stream
-            .read(&mut buf)
+    async fn read(&mut self) -> Result<Response, CommandError> {
+        read_frame(&mut self.stream)
             .await
-            .map_err(|_| CommandError::NoSessionTokenRead)?;
-
-        Ok(String::from_utf8_lossy(&buf[..n]).trim().to_string())
+            .map_err(CommandError::ReadFailure)?
+            .ok_or(CommandError::ConnectionFailure)
     }
 }
 
@@ -65,12 +143,11 @@ impl<T> Client for &mut T
 where
     T: Client + Send + ?Sized,
 {
-    async fn send(&mut self, msg: &str) -> Result<(), CommandError> {
-        (**self).send(msg).await
+    async fn send(&mut self, request: &Request) -> Result<(), CommandError> {
+        (**self).send(request).await
     }
 
-    async fn read(&mut self) -> Result<String, CommandError> {
+    async fn read(&mut self) -> Result<Response, CommandError> {
         (**self).read().await
     }
 }
-
@@ -0,0 +1,76 @@
+use async_trait::async_trait;
+use common::protocol::{GameEvent, Request, Response};
+use diplomacy::ShortName;
+
+use crate::{
+    auth::session::SessionKeeper,
+    commands::util::{describe_result, Client, Command, CommandError},
+};
+
+pub struct WatchCommand<C: Client, S: SessionKeeper> {
+    pub client: C,
+    session: S,
+}
+
+impl<C: Client, S: SessionKeeper> WatchCommand<C, S> {
+    pub fn new(client: C, session: S) -> Self {
+        Self { client, session }
+    }
+}
+
+fn print_event(event: &GameEvent) {
+    match event {
+        GameEvent::PlayerJoined { nation: Some(nation), .. } => println!("A new player has taken over {nation}"),
+        GameEvent::PlayerJoined { nation: None, .. } => println!("A player has joined the game"),
+        GameEvent::PlayerLeft { .. } => println!("A player has left the game"),
+        GameEvent::GameStarted { .. } => println!("The game has started, nations have been handed out"),
+        GameEvent::OrdersSubmitted { nation, .. } => println!("{nation} has submitted orders"),
+        GameEvent::ReadinessChanged { nation, ready: true, .. } => println!("{nation} is ready"),
+        GameEvent::ReadinessChanged { nation, ready: false, .. } => println!("{nation} is no longer ready"),
+        GameEvent::DrawVoteChanged { nation, draw: true, .. } => println!("{nation} has voted for a draw"),
+        GameEvent::DrawVoteChanged { nation, draw: false, .. } => println!("{nation} has withdrawn their draw vote"),
+        GameEvent::CivilDisorder { nation, .. } => {
+            println!("{nation} has fallen into civil disorder, a new player can join to take it over")
+        }
+        GameEvent::PhaseResolved { resolved, next, results, .. } => {
+            println!("{} has been resolved, now in {}", resolved.short_name(), next.short_name());
+            for result in results {
+                let mark = if result.succeeded { "ok" } else { "failed" };
+                println!("  [{mark}] {} ({})", result.order, result.outcome);
+            }
+        }
+        GameEvent::GameFinished { result, .. } => println!("The game has finished: {}", describe_result(result)),
+    }
+}
+
+#[async_trait]
+impl<C, S> Command for WatchCommand<C, S>
+where
+    C: Client + Send,
+    S: SessionKeeper + Send,
+{
+    async fn execute(&mut self) -> Result<(), CommandError> {
+        let session_token = self
+            .session
+            .load()
+            .ok_or(CommandError::NoSessionToken)?;
+
+        self.client.send(&Request::Watch { session: session_token }).await?;
+        match self.client.read().await? {
+            Response::Watching { game } => println!("Watching game {game}, press Ctrl+C to stop"),
+            other => return Err(other.into()),
+        }
+
+        loop {
+            match self.client.read().await? {
+                Response::Event(event) => {
+                    print_event(&event);
+                    if let GameEvent::GameFinished { .. } = event {
+                        return Ok(());
+                    }
+                }
+                other => return Err(other.into()),
+            }
+        }
+    }
+}
@@ -169,6 +169,7 @@ pub struct StateMachine {
 }
 
 impl StateMachine {
+    /// `game_context` has to be a player's, spectators have nothing to order
     pub fn new(inital_state:UiState, game_context: GameContext) -> Self {
         Self {
             data: MachineData { 
@@ -176,7 +177,9 @@ impl StateMachine {
                 selected_destination: None, 
                 order_intent: None, 
                 orders: Vec::new(), 
-                current_builder: OrderBuilder::new(&game_context.user_nation),
+                current_builder: OrderBuilder::new(
+                    game_context.user_nation.as_ref().expect("only players give orders, not spectators"),
+                ),
                 order_draft: None,
             },
             state: inital_state,
@@ -18,6 +18,10 @@ impl State for ShowUnitState {
     fn render(&self, _machine_data: &MachineData) {}
 
     fn handle_input(&mut self, _input: &str, data: &mut MachineData, ctx: &GameContext) -> InputResult {
+       let Some(nation) = &ctx.user_nation else {
+           println!("Spectators have no units to order!");
+           return InputResult::Quit;
+       };
        let Some(units) = ctx.remaining_units(&data.orders) else {
            println!("User has no possible moves!");
            return InputResult::Quit;
@@ -33,7 +37,7 @@ impl State for ShowUnitState {
        match select_from("Select command: ", &display_units) {
             SelectResult::Selected(unit) => {
                 data.selected_unit = UnitPosition::new(
-                    Unit::new(Cow::Owned(ctx.user_nation.clone()), unit.0), 
+                    Unit::new(Cow::Owned(nation.clone()), unit.0), 
                     unit.1).into();
                 InputResult::Advance
             }
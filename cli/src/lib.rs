@@ -5,11 +5,24 @@ pub mod auth;
 pub mod commands {
     pub mod connect;
     pub mod login; 
+    pub mod logout;
     pub mod join; 
+    pub mod leave;
+    pub mod switch;
+    pub mod spectate;
     pub mod order;
     pub mod map;
     pub mod register;
     pub mod create;
+    pub mod games;
+    pub mod watch;
+    pub mod history;
+    pub mod ready;
+    pub mod draw;
+    pub mod prefer;
+    pub mod start;
+    pub mod missing;
+    pub mod status;
     pub mod util;
 }
 
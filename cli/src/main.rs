@@ -4,12 +4,29 @@ use cli::auth::session::FileSessionKeeper;
 use cli::commands::util::{CommandError, TcpClient};
 use cli::commands::{
     login::LoginCommand,
+    logout::LogoutCommand,
     join::JoinCommand,
+    leave::LeaveCommand,
+    switch::SwitchCommand,
+    spectate::SpectateCommand,
     order::OrderCommand,
     register::RegisterCommand,
-    create::CreateCommand,
+    create::{parse_length, CreateCommand},
+    games::GamesCommand,
+    watch::WatchCommand,
+    history::HistoryCommand,
+    ready::ReadyCommand,
+    draw::DrawCommand,
+    prefer::PreferCommand,
+    start::StartCommand,
+    missing::MissingCommand,
+    status::StatusCommand,
 };
 use cli::commands::util::Command;
+use std::time::Duration;
+
+use common::context::MapKind;
+use common::protocol::{GameFilter, GameSettings, NationAssignment, PhaseDeadlines, PressMode};
 
 #[derive(Parser)]
 #[command(name = "terminal_diplomacy")]
@@ -32,18 +49,117 @@ enum Commands {
         username: String,
         password: String,
     },
+    /// End your session and forget the saved token
+    Logout {},
     Join {
         game: String,
+        /// The password or invite code for a private game
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// Leave a game that has not started yet, your current game unless one is given
+    Leave {
+        game: Option<String>,
+    },
+    /// Make another of your games the current one
+    Switch {
+        game: String,
     },
+    /// Follow a game without playing, status, watch and history then show it
+    Spectate {
+        game: String,
+        /// The password or invite code for a private game
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// Give or replace your orders for the current phase
     Order {
         #[arg(short, long)]
-        orders: Option<String>
+        orders: Option<String>,
+        /// Order in another of your games instead of the current one
+        #[arg(long)]
+        game: Option<String>,
+    },
+    /// Show the current phase, its deadline and who owns how many supply centres
+    Status {
+        /// Show another of your games instead of the current one
+        #[arg(long)]
+        game: Option<String>,
     },
+    /// List your units that have no orders yet, during movement they will hold
+    Missing {},
+    /// Say you are done with the current phase
+    Ready {},
+    /// Take back being ready so you can change your orders
+    Unready {},
+    /// Vote to end the game in a draw, it happens once every surviving player agrees
+    Draw {},
+    /// Take back your vote for a draw
+    Undraw {},
     Register {
         username: String,
         password: String,
     },
-    Create {},
+    /// Start a new game and join it, anything left out uses the server's defaults
+    Create {
+        /// Shown in the lobby
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long, default_value = "standard")]
+        variant: MapKind,
+        /// full, public or gunboat
+        #[arg(long, default_value = "full")]
+        press: PressMode,
+        /// How long movement phases last, e.g. 24h or 90m
+        #[arg(long, value_parser = parse_length)]
+        movement: Option<Duration>,
+        /// How long retreat phases last
+        #[arg(long, value_parser = parse_length)]
+        retreat: Option<Duration>,
+        /// How long build phases last
+        #[arg(long, value_parser = parse_length)]
+        build: Option<Duration>,
+        /// How many have to join before you can start the game early
+        #[arg(long)]
+        min_players: Option<usize>,
+        /// How many may join, the game starts once they have
+        #[arg(long)]
+        max_players: Option<usize>,
+        /// Make the game private, players need this password or invite code to join
+        #[arg(long)]
+        password: Option<String>,
+        /// How nations are handed out at the start, random or preferences
+        #[arg(long, default_value = "random")]
+        assignment: NationAssignment,
+        /// Seeds the random draw of nations so it can be repeated
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Rank the nations you would like to play, best first, e.g. prefer FRA ENG GER
+    Prefer {
+        #[arg(required = true)]
+        nations: Vec<String>,
+    },
+    /// Start your game before every seat is taken, only its creator can
+    Start {},
+    /// List the games still being played
+    Games {
+        /// Only games with a seat you could take
+        #[arg(long)]
+        open: bool,
+        /// Only games you are playing in
+        #[arg(long)]
+        mine: bool,
+        /// Games that are over instead
+        #[arg(long)]
+        finished: bool,
+    },
+    /// Print events from your current game as they happen
+    Watch {},
+    /// Show the orders and results of a past phase, e.g. S1901M
+    History {
+        phase: String,
+    },
 }
 
 #[tokio::main]
@@ -76,13 +192,68 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             cmd.execute().await
         }
 
-        Commands::Join { game } => {
-            let mut cmd = JoinCommand::new(client, &session, game);
+        Commands::Join { game, password } => {
+            let mut cmd = JoinCommand::new(client, &session, game, password);
+            cmd.execute().await
+        }
+
+        Commands::Leave { game } => {
+            let mut cmd = LeaveCommand::new(client, &session, game);
+            cmd.execute().await
+        }
+
+        Commands::Switch { game } => {
+            let mut cmd = SwitchCommand::new(client, &session, game);
+            cmd.execute().await
+        }
+
+        Commands::Spectate { game, password } => {
+            let mut cmd = SpectateCommand::new(client, &session, game, password);
+            cmd.execute().await
+        }
+
+        Commands::Order { orders, game } => {
+            let mut cmd = OrderCommand::new(client, &session, orders, game);
+            cmd.execute().await
+        }
+
+        Commands::Status { game } => {
+            let mut cmd = StatusCommand::new(client, &session, game);
+            cmd.execute().await
+        }
+
+        Commands::Missing {} => {
+            let mut cmd = MissingCommand::new(client, &session);
+            cmd.execute().await
+        }
+
+        Commands::Ready {} => {
+            let mut cmd = ReadyCommand::new(client, &session, true);
+            cmd.execute().await
+        }
+
+        Commands::Unready {} => {
+            let mut cmd = ReadyCommand::new(client, &session, false);
+            cmd.execute().await
+        }
+
+        Commands::Draw {} => {
+            let mut cmd = DrawCommand::new(client, &session, true);
+            cmd.execute().await
+        }
+
+        Commands::Undraw {} => {
+            let mut cmd = DrawCommand::new(client, &session, false);
+            cmd.execute().await
+        }
+
+        Commands::Prefer { nations } => {
+            let mut cmd = PreferCommand::new(client, &session, nations);
             cmd.execute().await
         }
 
-        Commands::Order { orders } => {
-            let mut cmd = OrderCommand::new(client, &session, orders);
+        Commands::Start {} => {
+            let mut cmd = StartCommand::new(client, &session);
             cmd.execute().await
         }
 
@@ -91,14 +262,49 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             cmd.execute().await
         }
 
-        Commands::Create {} => {
-            let mut cmd = CreateCommand::new(client, &session);
+        Commands::Create {
+            name, variant, press, movement, retreat, build, min_players, max_players, password, assignment, seed,
+        } => {
+            // Lengths that were not given keep the standard ones
+            let deadlines = (movement.is_some() || retreat.is_some() || build.is_some()).then(|| {
+                let standard = PhaseDeadlines::default();
+                PhaseDeadlines {
+                    movement: movement.unwrap_or(standard.movement),
+                    retreat: retreat.unwrap_or(standard.retreat),
+                    build: build.unwrap_or(standard.build),
+                }
+            });
+            let settings = GameSettings {
+                name, variant, press, deadlines, min_players, max_players, password, assignment, seed,
+            };
+            let mut cmd = CreateCommand::new(client, &session, settings);
+            cmd.execute().await
+        }
+
+        Commands::Games { open, mine, finished } => {
+            let filter = GameFilter { open_seats: open, mine, finished };
+            let mut cmd = GamesCommand::new(client, &session, filter);
+            cmd.execute().await
+        }
+
+        Commands::Logout {} => {
+            let mut cmd = LogoutCommand::new(client, &session);
+            cmd.execute().await
+        }
+
+        Commands::History { phase } => {
+            let mut cmd = HistoryCommand::new(client, &session, phase);
+            cmd.execute().await
+        }
+
+        Commands::Watch {} => {
+            let mut cmd = WatchCommand::new(client, &session);
             cmd.execute().await
         }
     };
 
     if let Err(err) = result {
-        eprintln!("Error: {:?}", err);
+        eprintln!("Error: {err}");
     }
     
     Ok(())
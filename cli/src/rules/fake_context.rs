@@ -74,7 +74,7 @@ pub fn fake_game_context_france() -> GameContext {
     }
 
     GameContext::new(
-        user_nation,
+        Some(user_nation),
         MapKind::Standard,
         last_owners,
         occupiers,
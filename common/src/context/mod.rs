@@ -1,27 +1,85 @@
 use std::{borrow::Cow, collections::{HashMap, HashSet}};
 
-use diplomacy::{Nation, ShortName, Unit, UnitPosition, UnitType, geo::{Map, ProvinceKey, RegionKey, standard_map}, judge::MappedMainOrder};
+use diplomacy::{Nation, Time, Unit, UnitPosition, UnitType, geo::{Map, ProvinceKey, RegionKey, standard_map}, judge::MappedMainOrder};
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+use crate::protocol::GameResult;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum MapKind {
     // more can be added in the future
+    #[default]
     Standard,
 }
 
+impl std::str::FromStr for MapKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "standard" => Ok(MapKind::Standard),
+            other => Err(format!("unknown variant {other}, expected standard")),
+        }
+    }
+}
+
+impl MapKind {
+    /// How many nations there are to play
+    pub fn nations(&self) -> usize {
+        match self {
+            MapKind::Standard => 7,
+        }
+    }
+
+    /// How many supply centres a nation needs to win outright, 18 of the 34 on the standard map
+    pub fn victory_centres(&self) -> usize {
+        match self {
+            MapKind::Standard => 18,
+        }
+    }
+}
+
+/// How many supply centres each nation held once the Fall of `year` was over
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SupplyCentreCount {
+    pub year: usize,
+    pub centres: HashMap<Nation, usize>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameContext {
-    pub user_nation: Nation,
+    /// `None` for spectators, who see the board but play no nation
+    pub user_nation: Option<Nation>,
     map: MapKind,
     last_owners: HashMap<ProvinceKey, Nation>, 
     occupiers: HashMap<ProvinceKey, Nation>,
     pub units: HashMap<Nation, HashSet<(UnitType, RegionKey)>>,
+    /// Supply centres each nation owns right now
+    #[serde(default)]
+    pub supply_centres: HashMap<Nation, usize>,
+    /// The counts at the end of every year played so far, oldest first
+    #[serde(default)]
+    pub supply_centre_history: Vec<SupplyCentreCount>,
+    /// The phase the game is waiting on
+    #[serde(default)]
+    pub time: Option<Time>,
+    /// When the phase is adjudicated whether or not everyone is ready, there is none
+    /// until enough players have joined for the game to start
+    #[serde(default, with = "time::serde::timestamp::option")]
+    pub deadline: Option<OffsetDateTime>,
+    /// Nations whose player has gone missing, their units hold until someone takes over
+    #[serde(default)]
+    pub civil_disorder: HashSet<Nation>,
+    /// How the game ended, `None` while it is still being played
+    #[serde(default)]
+    pub result: Option<GameResult>,
 }
 
 impl GameContext {
     pub fn new(
-        user_nation: Nation, 
+        user_nation: Option<Nation>, 
         map_kind: MapKind,
         last_owners: HashMap<ProvinceKey, Nation>, 
         occupiers: HashMap<ProvinceKey, Nation>,
@@ -33,9 +91,41 @@ impl GameContext {
             last_owners: last_owners,
             occupiers: occupiers,
             units: units,
+            supply_centres: HashMap::new(),
+            supply_centre_history: Vec::new(),
+            time: None,
+            deadline: None,
+            civil_disorder: HashSet::new(),
+            result: None,
         }
     }
 
+    pub fn with_supply_centres(mut self, counts: HashMap<Nation, usize>, history: Vec<SupplyCentreCount>) -> Self {
+        self.supply_centres = counts;
+        self.supply_centre_history = history;
+        self
+    }
+
+    pub fn with_time(mut self, time: Time) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    pub fn with_deadline(mut self, deadline: Option<OffsetDateTime>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    pub fn with_civil_disorder(mut self, nations: HashSet<Nation>) -> Self {
+        self.civil_disorder = nations;
+        self
+    }
+
+    pub fn with_result(mut self, result: Option<GameResult>) -> Self {
+        self.result = result;
+        self
+    }
+
     fn adapt_orders(&self, orders: Vec<MappedMainOrder>) -> HashSet<(UnitType, RegionKey)> {
         orders
             .iter()
@@ -49,7 +139,7 @@ impl GameContext {
     }
 
     pub fn find_player_units(&self) -> Option<&HashSet<(UnitType, RegionKey)>> {
-        self.units.get(&self.user_nation)
+        self.units.get(self.user_nation.as_ref()?)
     }
 
     pub fn remaining_units(&self, orders: &Vec<MappedMainOrder>) -> Option<HashSet<(UnitType, RegionKey)>> {
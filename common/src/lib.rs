@@ -1,2 +1,3 @@
 pub mod hash;
-pub mod context;
\ No newline at end of file
+pub mod context;
+pub mod protocol;
\ No newline at end of file
@@ -0,0 +1,59 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Stable identifiers for everything that can go wrong with a request.
+///
+/// Clients should match on these rather than on the message, which is only meant for humans
+/// and may change at any time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "code", content = "details", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    MalformedRequest,
+    InvalidCredentials,
+    SessionUnknown,
+    SessionExpired,
+    NotInGame,
+    GameNotFound,
+    GameFull,
+    AlreadyJoined,
+    GameFinished,
+    /// Nations have been handed out, so the game can no longer be joined or started
+    GameStarted,
+    /// Nothing can be ordered until the game starts and nations are handed out
+    GameNotStarted,
+    /// Only the player who created the game can start it early
+    NotHost,
+    /// Fewer players have joined than the game needs to start
+    NotEnoughPlayers,
+    /// The game needs a password or invite code and the one given was missing or wrong
+    WrongPassword,
+    /// The settings asked for when creating a game cannot be used
+    InvalidSettings,
+    WrongPhase,
+    OrderCountMismatch { expected: usize, found: usize },
+    InvalidOrders,
+    Database,
+    Internal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    #[serde(flatten)]
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl ErrorResponse {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+impl fmt::Display for ErrorResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ErrorResponse {}
@@ -0,0 +1,129 @@
+use std::fmt;
+
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Frames larger than this are rejected rather than allocated
+pub const MAX_FRAME_LEN: u32 = 1 << 20;
+
+#[derive(Debug)]
+pub enum FrameError {
+    Io(std::io::Error),
+    TooLarge(u32),
+    Malformed(serde_json::Error),
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameError::Io(e) => write!(f, "connection error: {e}"),
+            FrameError::TooLarge(len) => write!(f, "frame of {len} bytes exceeds the {MAX_FRAME_LEN} byte limit"),
+            FrameError::Malformed(e) => write!(f, "malformed message: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for FrameError {}
+
+impl From<std::io::Error> for FrameError {
+    fn from(e: std::io::Error) -> Self {
+        FrameError::Io(e)
+    }
+}
+
+/// Writes `message` as a frame: a big endian u32 length followed by that many bytes of JSON
+pub async fn write_frame<W, T>(writer: &mut W, message: &T) -> Result<(), FrameError>
+where
+    W: AsyncWrite + Unpin,
+    T: Serialize,
+{
+    let body = serde_json::to_vec(message).map_err(FrameError::Malformed)?;
+    let len = u32::try_from(body.len()).unwrap_or(u32::MAX);
+    if len > MAX_FRAME_LEN {
+        return Err(FrameError::TooLarge(len));
+    }
+
+    writer.write_all(&len.to_be_bytes()).await?;
+    writer.write_all(&body).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Reads the next frame, returning `None` if the peer closed the connection cleanly
+/// before a new frame started
+pub async fn read_frame<R, T>(reader: &mut R) -> Result<Option<T>, FrameError>
+where
+    R: AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    let mut len_buf = [0u8; 4];
+    match reader.read_exact(&mut len_buf).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+
+    let len = u32::from_be_bytes(len_buf);
+    if len > MAX_FRAME_LEN {
+        return Err(FrameError::TooLarge(len));
+    }
+
+    let mut body = vec![0u8; len as usize];
+    reader.read_exact(&mut body).await?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(FrameError::Malformed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn frames_round_trip() {
+        let mut buf = Vec::new();
+        write_frame(&mut buf, &vec!["hello".to_string(), "world".to_string()]).await.unwrap();
+        write_frame(&mut buf, &42u32).await.unwrap();
+
+        let mut reader = buf.as_slice();
+        let first: Option<Vec<String>> = read_frame(&mut reader).await.unwrap();
+        let second: Option<u32> = read_frame(&mut reader).await.unwrap();
+        assert_eq!(first, Some(vec!["hello".to_string(), "world".to_string()]));
+        assert_eq!(second, Some(42));
+    }
+
+    #[tokio::test]
+    async fn clean_eof_is_none() {
+        let mut reader: &[u8] = &[];
+        let frame: Option<u32> = read_frame(&mut reader).await.unwrap();
+        assert!(frame.is_none());
+    }
+
+    #[tokio::test]
+    async fn eof_inside_a_frame_is_an_error() {
+        let mut buf = Vec::new();
+        write_frame(&mut buf, &"truncated").await.unwrap();
+        buf.truncate(buf.len() - 2);
+
+        let mut reader = buf.as_slice();
+        let frame: Result<Option<String>, _> = read_frame(&mut reader).await;
+        assert!(matches!(frame, Err(FrameError::Io(_))));
+    }
+
+    #[tokio::test]
+    async fn oversized_frames_are_rejected_before_reading() {
+        let len = MAX_FRAME_LEN + 1;
+        let mut reader: &[u8] = &len.to_be_bytes();
+        let frame: Result<Option<String>, _> = read_frame(&mut reader).await;
+        assert!(matches!(frame, Err(FrameError::TooLarge(found)) if found == len));
+    }
+
+    #[tokio::test]
+    async fn oversized_messages_are_not_written() {
+        let mut buf = Vec::new();
+        let message = "x".repeat(MAX_FRAME_LEN as usize);
+        let written = write_frame(&mut buf, &message).await;
+        assert!(matches!(written, Err(FrameError::TooLarge(_))));
+        assert!(buf.is_empty());
+    }
+}
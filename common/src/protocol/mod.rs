@@ -0,0 +1,379 @@
+//! The wire protocol shared by the server and the CLI.
+//!
+//! Every message is a single JSON encoded `Request` or `Response` carried inside a
+//! length prefixed frame (see `frame`), so order JSON can contain any character and
+//! be any size up to `frame::MAX_FRAME_LEN`.
+
+use diplomacy::judge::{MappedBuildOrder, MappedMainOrder, MappedRetreatOrder};
+use diplomacy::geo::RegionKey;
+use diplomacy::{Nation, Phase, Time, UnitType};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::context::{GameContext, MapKind};
+
+pub mod error;
+pub mod frame;
+
+pub use error::{ErrorCode, ErrorResponse};
+pub use frame::{read_frame, write_frame, FrameError};
+
+type SessionId = Uuid;
+type GameId = Uuid;
+
+/// The orders for one phase, tagged with the phase they are meant for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "phase", content = "orders", rename_all = "snake_case")]
+pub enum OrderSet {
+    Main(Vec<MappedMainOrder>),
+    Retreat(Vec<MappedRetreatOrder>),
+    Build(Vec<MappedBuildOrder>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    Login { username: String, password: String },
+    Register { username: String, password: String },
+    /// Ends the session so its token can no longer be used
+    Logout { session: SessionId },
+    /// `password` is needed for games created with one, it is the password or invite code
+    /// the creator shared
+    Join {
+        session: SessionId,
+        game: GameId,
+        #[serde(default)]
+        password: Option<String>,
+    },
+    Create {
+        session: SessionId,
+        #[serde(default)]
+        settings: GameSettings,
+    },
+    /// Ranks the nations the player would like, best first, for games that hand them out
+    /// by preference. Can be changed until the game starts
+    Preferences { session: SessionId, nations: Vec<Nation> },
+    /// Starts the session's game before every seat is taken, only its creator can once
+    /// enough players have joined
+    Start { session: SessionId },
+    /// Takes the player back out of a game that has not started yet, `game` defaults to the
+    /// session's current game
+    Leave {
+        session: SessionId,
+        #[serde(default)]
+        game: Option<GameId>,
+    },
+    /// Makes another game the player is in the session's current game
+    Switch { session: SessionId, game: GameId },
+    /// Follows a game without playing, it becomes the session's current game so the board,
+    /// events and history can be seen but nothing can be ordered. Private games need `password`
+    Spectate {
+        session: SessionId,
+        game: GameId,
+        #[serde(default)]
+        password: Option<String>,
+    },
+    /// Saves orders for the current phase. Movement orders can cover just some units and
+    /// replace any earlier order for the same unit, units never ordered hold. `game` picks
+    /// one of the player's games other than the current one
+    Order {
+        session: SessionId,
+        orders: OrderSet,
+        #[serde(default)]
+        game: Option<GameId>,
+    },
+    /// Which of the player's units have no orders yet this phase
+    MissingOrders { session: SessionId },
+    /// Says whether the player is done with the current phase, it resolves once everyone is.
+    /// Dislodged units left without orders disband and unused builds are waived
+    Ready { session: SessionId, ready: bool },
+    /// Votes for or against ending the game in a draw between every surviving nation,
+    /// it is declared once all the players still taking part have voted for it
+    Draw { session: SessionId, draw: bool },
+    /// The player's view of the session's current game, or of `game` if given
+    Context {
+        session: SessionId,
+        #[serde(default)]
+        game: Option<GameId>,
+    },
+    /// The games that can be seen in the lobby, narrowed down by `filter`
+    ListGames { session: SessionId, filter: GameFilter },
+    /// Subscribe this connection to the events of the session's current game
+    Watch { session: SessionId },
+    /// The archived orders and results of one phase of the session's current game
+    History { session: SessionId, phase: Time },
+}
+
+impl Request {
+    /// The session a request was made under, if it needs one
+    pub fn session(&self) -> Option<SessionId> {
+        match self {
+            Request::Login { .. } | Request::Register { .. } => None,
+            Request::Join { session, .. }
+            | Request::Create { session, .. }
+            | Request::Preferences { session, .. }
+            | Request::Start { session }
+            | Request::Leave { session, .. }
+            | Request::Switch { session, .. }
+            | Request::Spectate { session, .. }
+            | Request::Order { session, .. }
+            | Request::Ready { session, .. }
+            | Request::Draw { session, .. }
+            | Request::MissingOrders { session }
+            | Request::Context { session, .. }
+            | Request::ListGames { session, .. }
+            | Request::Watch { session }
+            | Request::History { session, .. }
+            | Request::Logout { session } => Some(*session),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Response {
+    /// Sent back after a successful login or registration
+    Session { token: SessionId, user: UserInfo },
+    LoggedOut,
+    Joined { game: GameId },
+    Created { game: GameId },
+    PreferencesSaved,
+    Started { game: GameId },
+    /// `current` is the session's game afterwards, another of the player's games if they
+    /// left the one they had picked
+    Left { game: GameId, current: Option<GameId> },
+    Switched { game: GameId },
+    Spectating { game: GameId },
+    /// The orders were saved, they can be replaced until the phase resolves
+    OrdersAccepted,
+    ReadyChanged { ready: bool },
+    DrawVoteChanged { draw: bool },
+    MissingOrders { units: Vec<(UnitType, RegionKey)> },
+    /// The player was the last one needed to be ready and the phase was resolved
+    PhaseAdvanced,
+    /// The request ended the game
+    GameFinished { result: GameResult },
+    Context(Box<GameContext>),
+    Games { games: Vec<GameSummary> },
+    Watching { game: GameId },
+    History(PhaseHistory),
+    /// Pushed by the server to connections watching a game, never a reply to a request
+    Event(GameEvent),
+    Error(ErrorResponse),
+}
+
+/// Who a session belongs to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserInfo {
+    pub id: Uuid,
+    pub username: String,
+}
+
+/// Who players may send messages to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PressMode {
+    /// Private and public messages
+    #[default]
+    Full,
+    /// Only messages everyone can read
+    Public,
+    /// No messages at all
+    Gunboat,
+}
+
+impl std::str::FromStr for PressMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "full" => Ok(PressMode::Full),
+            "public" => Ok(PressMode::Public),
+            "gunboat" | "none" => Ok(PressMode::Gunboat),
+            other => Err(format!("unknown press mode {other}, expected full, public or gunboat")),
+        }
+    }
+}
+
+/// How nations are handed out when a game starts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NationAssignment {
+    #[default]
+    Random,
+    /// Players rank the nations they want with `Request::Preferences`
+    Preferences,
+}
+
+impl std::str::FromStr for NationAssignment {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "random" => Ok(NationAssignment::Random),
+            "preferences" => Ok(NationAssignment::Preferences),
+            other => Err(format!("unknown assignment {other}, expected random or preferences")),
+        }
+    }
+}
+
+/// What a new game is set up with, anything left out falls back to the server's defaults
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameSettings {
+    /// Shown in the lobby, the game id is used if there is none
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub variant: MapKind,
+    #[serde(default)]
+    pub press: PressMode,
+    #[serde(default)]
+    pub deadlines: Option<PhaseDeadlines>,
+    /// The game starts once this many have joined, every seat by default
+    #[serde(default)]
+    pub min_players: Option<usize>,
+    /// How many may join, one per nation by default
+    #[serde(default)]
+    pub max_players: Option<usize>,
+    /// A password or invite code players need to join
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub assignment: NationAssignment,
+    /// Seeds the draw of nations so it can be repeated, a random one is used if there is none
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+/// Which games `ListGames` returns. With nothing set every game still being played is listed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameFilter {
+    /// Only games with a seat free, including nations in civil disorder waiting for a new player
+    #[serde(default)]
+    pub open_seats: bool,
+    /// Only games the user is playing in
+    #[serde(default)]
+    pub mine: bool,
+    /// Games that have been won or drawn instead of the ones still being played
+    #[serde(default)]
+    pub finished: bool,
+}
+
+/// A game as it appears in the lobby
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameSummary {
+    pub id: GameId,
+    pub name: String,
+    pub players: usize,
+    /// Players needed before the game starts
+    pub min_players: usize,
+    pub seats: usize,
+    /// Seats that can be taken right now, nations in civil disorder count
+    pub open_seats: usize,
+    pub time: Time,
+    pub variant: MapKind,
+    pub press: PressMode,
+    pub deadlines: PhaseDeadlines,
+    pub assignment: NationAssignment,
+    /// Whether joining needs a password or invite code
+    pub private: bool,
+    /// Whether nations have been handed out, after that only abandoned ones can be joined
+    pub started: bool,
+    /// Whether the user asking is playing in it
+    pub joined: bool,
+    #[serde(default)]
+    pub spectators: usize,
+    pub result: Option<GameResult>,
+}
+
+/// How a single order fared during adjudication
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderResult {
+    pub order: String,
+    pub succeeded: bool,
+    pub outcome: String,
+}
+
+/// The final orders each nation gave in a phase
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NationOrders {
+    pub nation: Nation,
+    pub orders: Vec<String>,
+}
+
+/// Everything that was ordered in a past phase and how it turned out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhaseHistory {
+    pub game: GameId,
+    pub phase: Time,
+    /// Empty until the phase has been resolved, orders are secret until then
+    pub orders: Vec<NationOrders>,
+    /// Empty if the phase has not been resolved yet
+    pub results: Vec<OrderResult>,
+}
+
+const HOUR: u64 = 60 * 60;
+
+/// How long players get for each kind of phase before it is adjudicated without them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhaseDeadlines {
+    pub movement: Duration,
+    pub retreat: Duration,
+    pub build: Duration,
+}
+
+impl PhaseDeadlines {
+    pub fn for_phase(&self, phase: Phase) -> Duration {
+        match phase {
+            Phase::Main => self.movement,
+            Phase::Retreat => self.retreat,
+            Phase::Build => self.build,
+        }
+    }
+}
+
+impl Default for PhaseDeadlines {
+    fn default() -> Self {
+        Self {
+            movement: Duration::from_secs(24 * HOUR),
+            retreat: Duration::from_secs(HOUR),
+            build: Duration::from_secs(HOUR),
+        }
+    }
+}
+
+/// How a game ended
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GameResult {
+    /// One nation held enough supply centres to win outright
+    Victory { winner: Nation },
+    /// The surviving nations agreed to share the game
+    Draw { nations: Vec<Nation> },
+}
+
+/// Something that happened in a game that watchers should hear about
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum GameEvent {
+    /// `nation` is `None` while the game waits to start
+    PlayerJoined { game: GameId, nation: Option<Nation> },
+    /// Every player now has a nation and the first phase is under way
+    GameStarted { game: GameId },
+    /// A player waiting for the game to start has left it
+    PlayerLeft { game: GameId },
+    OrdersSubmitted { game: GameId, nation: Nation },
+    ReadinessChanged { game: GameId, nation: Nation, ready: bool },
+    DrawVoteChanged { game: GameId, nation: Nation, draw: bool },
+    /// The nation's player missed too many deadlines in a row, anyone may join to take it over
+    CivilDisorder { game: GameId, nation: Nation },
+    PhaseResolved {
+        game: GameId,
+        resolved: Time,
+        next: Time,
+        results: Vec<OrderResult>,
+    },
+    /// Nothing more can be ordered once this is sent
+    GameFinished { game: GameId, result: GameResult },
+}
@@ -1,12 +1,18 @@
 use crate::data::connection_pool::ConnectionPool;
 use crate::game::game_handler::OrderOutcome;
+use crate::game::game_settings::CreateError;
+use crate::network::connection::{ConnectionHandle, ConnectionId};
 use crate::order::order_collector;
 use crate::order::order_service::OrderService;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::data::user::{self, ActiveModel as ActiveUserModel, Column as UserColumn, Entity as User, Model as UserModel};
 use crate::data::game::{self, ActiveModel as ActiveGameModel, Column as GameColumn, Entity as Game, Model as GameModel};
 use common::context::GameContext;
+use common::protocol::{ErrorCode, ErrorResponse, GameFilter, GameSettings, GameSummary, OrderSet, PhaseHistory, Request, Response, UserInfo};
+use diplomacy::geo::RegionKey;
+use diplomacy::{Nation, Time, UnitType};
 use diplomacy::judge::{MappedBuildOrder, MappedMainOrder, MappedRetreatOrder};
 use sea_orm::{EntityTrait, ColumnTrait, QueryFilter};
 use sea_orm::DbErr;
@@ -18,7 +24,8 @@ use uuid::{Uuid};
 use sea_orm::{ActiveModelTrait, ActiveValue::NotSet, Database, DatabaseConnection, Set};
 use common::hash::hash_password;
 use common::hash::verify_password;
-use crate::auth::session::SessionStore;
+use crate::auth::session::{Session, SessionStore, SessionTtl};
+use time::OffsetDateTime;
 
 use once_cell::sync::Lazy;
 use tokio::sync::RwLock;
@@ -31,24 +38,104 @@ pub type SharedSessionStore = Arc<RwLock<dyn SessionStore>>;
 pub struct ConnectionsManager {
     pool: Arc<ConnectionPool>, // This in teh future should just be replaced with the proper srvices
     session_store: SharedSessionStore,
+    session_ttl: SessionTtl,
     game_service: Arc<GameService>,
-    order_service: Arc<OrderService>
+    order_service: Arc<OrderService>,
+    connections: RwLock<HashMap<ConnectionId, ConnectionHandle>>,
 }
 
 impl ConnectionsManager {
-    pub fn new(pool: Arc<ConnectionPool>, session_store: SharedSessionStore, game_service: Arc<GameService>, order_service: Arc<OrderService>) -> Self {
-        Self { pool, session_store, game_service, order_service }
+    pub fn new(pool: Arc<ConnectionPool>, session_store: SharedSessionStore, session_ttl: SessionTtl, game_service: Arc<GameService>, order_service: Arc<OrderService>) -> Self {
+        Self { pool, session_store, session_ttl, game_service, order_service, connections: RwLock::new(HashMap::new()) }
     }
 
-    pub async fn handle_login(&self, username: String, password: String) -> Result<Option<UserModel>, sea_orm::DbErr> {
+    /// Starts tracking a newly accepted client
+    pub async fn register_connection(&self, handle: ConnectionHandle) -> ConnectionId {
+        let connection_id = ConnectionId::new_v4();
+        self.connections.write().await.insert(connection_id, handle);
+        connection_id
+    }
+
+    /// Forgets a client once its socket has closed. The session itself outlives the
+    /// connection so the user can reconnect with the same token.
+    pub async fn disconnect(&self, connection_id: &ConnectionId) {
+        self.connections.write().await.remove(connection_id);
+    }
+
+    async fn bind_session(&self, connection_id: &ConnectionId, session_id: Option<Uuid>) {
+        if let Some(handle) = self.connections.write().await.get_mut(connection_id) {
+            handle.session = session_id;
+        }
+    }
+
+    /// Checks the session is still alive and marks it as just used. Expired sessions
+    /// are removed straight away rather than waiting for the sweeper.
+    async fn refresh_session(&self, session_id: &Uuid) -> Result<(), ErrorResponse> {
+        let now = OffsetDateTime::now_utc();
+        let session = self.session(session_id).await?;
+        if session.is_expired(now, &self.session_ttl) {
+            self.session_store.write().await.delete(session_id).await?;
+            self.stop_spectating(&session).await;
+            return Err(ErrorResponse::new(ErrorCode::SessionExpired, "The session has expired"));
+        }
+        if session.needs_touch(now) {
+            self.session_store.write().await.touch(session_id, now).await?;
+        }
+        Ok(())
+    }
+
+    async fn session(&self, session_id: &Uuid) -> Result<Session, ErrorResponse> {
+        self.session_store.read().await
+            .get(session_id)
+            .await?
+            .ok_or_else(session_unknown)
+    }
+
+    /// The session as a request naming `game` should see it, with that game as the current
+    /// one for just this request. The user has to be in the game
+    async fn session_for_game(&self, session_id: &Uuid, game: Option<Uuid>) -> Result<Session, ErrorResponse> {
+        let mut session = self.session(session_id).await?;
+        if let Some(game_id) = game {
+            if !self.game_service.is_member(&game_id, session.user).await.map_err(database_error)? {
+                return Err(ErrorResponse::new(ErrorCode::NotInGame, "You are not in that game"));
+            }
+            session.current_game = Some(game_id);
+        }
+        Ok(session)
+    }
+
+    /// Stops the user spectating the session's current game, for when they move on from it
+    async fn stop_spectating(&self, session: &Session) {
+        self.game_service.forget_spectator(session).await;
+    }
+
+    /// Saves a game after a request changed it. The change has already happened in memory,
+    /// so a failure here is logged rather than sent back to the player.
+    async fn persist_game(&self, game_id: &Uuid) {
+        if let Err(e) = self.game_service.save_game(game_id).await {
+            eprintln!("[DB_ERROR] Failed to save game {game_id}: {e}");
+        }
+    }
+
+    /// Checks the credentials and starts a new session for the user, returning `None`
+    /// if the username or password is wrong
+    pub async fn handle_login(&self, username: String, password: String) -> Result<Option<(Uuid, UserInfo)>, ErrorResponse> {
         let conn = self.pool.get_connection();
         if let Some(user) = User::find()
             .filter(UserColumn::Username.eq(username))
             .one(conn)
-            .await?
+            .await
+            .map_err(database_error)?
             {
                 if verify_password(&password, &user.password_hash) {
-                    return Ok(Some(user));
+                    // Pick up the game the user was last playing
+                    let last_game = self.game_service.last_selected_game(user.user_uuid).await.map_err(database_error)?;
+                    let mut session_store = self.session_store.write().await;
+                    let session_id = session_store.create(user.user_uuid).await?;
+                    if last_game.is_some() {
+                        session_store.set_current_game(&session_id, last_game).await?;
+                    }
+                    return Ok(Some((session_id, user_info(&user))));
                 }
                 
             }
@@ -56,145 +143,412 @@ impl ConnectionsManager {
     }
 
    
-    pub async fn handle_registration(&self, username: String, password: String) -> Result<Uuid, sea_orm::DbErr> {
+    pub async fn handle_registration(&self, username: String, password: String) -> Result<(Uuid, UserInfo), ErrorResponse> {
         let conn = self.pool.get_connection();
         let hashed_password = hash_password(&password);
-        let user = Uuid::new_v4();
         let user_model = ActiveUserModel {
             user_id: NotSet,
+            user_uuid: Set(Uuid::new_v4()),
             username: Set(username),
             password_hash: Set(hashed_password),
             created_at: NotSet,
         };
 
-        user_model.insert(conn).await?;
+        let user = user_model.insert(conn).await.map_err(database_error)?;
 
         // Create the session for the user 
         let mut session_store = self.session_store.write().await;
-        let res = session_store.create(user);
-        Ok(res)
+        let res = session_store.create(user.user_uuid).await?;
+        Ok((res, user_info(&user)))
     }
 
-    pub async fn handle_join(&self, game_str: &str, session_id: Uuid ) -> Result<Uuid, String>{
-        let game_id = match Uuid::parse_str(game_str) {
-            Ok(id) => id,
-            Err(e) => {
-                eprint!("This is annoying {e}");
-                return Err(format!("Failed to parse UUID: {}", e));
-            }
-        };
+    pub async fn handle_logout(&self, session_id: Uuid) -> Result<(), ErrorResponse> {
+        let session = self.session_store.write().await
+            .delete(&session_id)
+            .await?
+            .ok_or_else(session_unknown)?;
+        self.stop_spectating(&session).await;
+        Ok(())
+    }
 
-        let mut session_store = self.session_store.write().await;
-        let user_session = session_store.get_mut(&session_id).expect("Something has gone wrong and the session is not found");
+    pub async fn handle_join(&self, game_id: Uuid, session_id: Uuid, password: Option<String>) -> Result<Uuid, ErrorResponse>{
+        let mut user_session = self.session(&session_id).await?;
         
-        match self.game_service.join_game( &game_id,user_session.user ).await {
-            Ok(()) => {println!("Game joined succesffully ");}
-            Err(e) => {
-                println!("lol not dealing with this {e}");
-                return Err(format!("Failed to find game with GameID: {}", e));
-            }
-        }   
+        self.game_service.join_game(&game_id, user_session.user, password.as_deref()).await?;
 
+        self.stop_spectating(&user_session).await;
         user_session.current_game = Some(game_id);
+        self.session_store.write().await.set_current_game(&session_id, user_session.current_game).await?;
+        self.persist_game(&game_id).await;
         println!("Debug!: This is the contents of the session: {:?}", user_session);
-        Ok(session_id)
-        
-    }
+        Ok(game_id)
 
-    pub async fn handle_create(&self, session_id: Uuid) -> Result<Uuid, String> {
-        let game_id = self.game_service.create_game().await;
+    }
 
-        // Adds the user to the game on the the session
-        let mut session_store = self.session_store.write().await;
-        let user_session = session_store.get_mut(&session_id).expect("Something has gone wrong and the session is not found");
+    pub async fn handle_create(&self, session_id: Uuid, settings: GameSettings) -> Result<Uuid, ErrorResponse> {
+        // Check the session before making a game nobody can join
+        let mut user_session = self.session(&session_id).await?;
+        let game_id = self.game_service
+            .create_game(&settings)
+            .await
+            .map_err(|e| match e {
+                CreateError::Settings(e) => e.into(),
+                CreateError::Database(e) => database_error(e),
+            })?;
+
+        // Adds the user to the game on the the session, a game its creator could not join is dropped
+        if let Err(e) = self.game_service.join_game(&game_id, user_session.user, settings.password.as_deref()).await {
+            self.game_service.discard_game(&game_id).await;
+            return Err(e.into());
+        }
+        self.stop_spectating(&user_session).await;
         user_session.current_game = Some(game_id);
-
-        self.game_service.join_game(&game_id, user_session.user).await.expect("This should always be possible");
+        self.session_store.write().await.set_current_game(&session_id, user_session.current_game).await?;
+        self.persist_game(&game_id).await;
 
         // Update the session for the user as they added to a game
         println!("[DEBUG]: This is the contents of the session: {:?}", user_session);
-        Ok(session_id)
+        Ok(game_id)
     }
 
-    pub async fn handle_main_order(&self, session_id: Uuid, orders_str: &str) -> Result<Uuid, String> {
-        // I am doing the order conversion here because it is the job of the connection manager
-        // to handle types and parsing... for now
-
-        let orders: Vec<MappedMainOrder> = serde_json::from_str(orders_str)
-            .map_err(|e| format!("Failed to convert {} into json, {}", orders_str, e))?;
+    pub async fn handle_main_order(&self, session_id: Uuid, game: Option<Uuid>, orders: Vec<MappedMainOrder>) -> Result<OrderOutcome, ErrorResponse> {
         println!("[DEBUG] Orders parsed {:?}", orders);
 
         // Now that it is finalized, we get the session 
-        let mut session_store = self.session_store.write().await;
-        let user_session = session_store.get_mut(&session_id).expect("Something has gone wrong and the session is not found");
-        
-        // Sanity check
-        assert!(user_session.current_game.is_some());
+        let user_session = self.session_for_game(&session_id, game).await?;
         let res = self.order_service
             .send_main_order(&user_session, orders)
-            .await
-            .map_err(|e| format!("Failed to submit main order: {:?}", e))?;
+            .await?;
 
-        match res {
-            OrderOutcome::Accepted => {println!("Correctly added the order!")}
-            _ => {}
+        if let OrderOutcome::Accepted = res {
+            println!("Correctly added the order!");
+        }
+        if let Some(game_id) = user_session.current_game {
+            self.persist_game(&game_id).await;
         }
-        Ok(Uuid::max())
+        Ok(res)
     }
 
-    pub async fn handle_retreat_order(&self, session_id: Uuid, orders_str: &str) -> Result<Uuid, String> {
-        let orders: Vec<MappedRetreatOrder> = serde_json::from_str(orders_str)
-            .map_err(|e| format!("Failed to convert {} into json, {}", orders_str, e))?;
+    pub async fn handle_retreat_order(&self, session_id: Uuid, game: Option<Uuid>, orders: Vec<MappedRetreatOrder>) -> Result<OrderOutcome, ErrorResponse> {
         println!("[DEBUG] Orders parsed {:?}", orders);
 
         // Now that it is finalized, we get the session 
-        let mut session_store = self.session_store.write().await;
-        let user_session = session_store.get_mut(&session_id).expect("Something has gone wrong and the session is not found");
-        
-        // Sanity check
-        assert!(user_session.current_game.is_some());
+        let user_session = self.session_for_game(&session_id, game).await?;
         let res = self.order_service
             .send_retreat_order(&user_session, orders)
-            .await
-            .map_err(|e| format!("Failed to submit retreat order: {:?}", e))?;
+            .await?;
 
-        match res {
-            OrderOutcome::Accepted => {println!("Correctly added the order!")}
-            _ => {}
+        if let OrderOutcome::Accepted = res {
+            println!("Correctly added the order!");
+        }
+        if let Some(game_id) = user_session.current_game {
+            self.persist_game(&game_id).await;
         }
-        Ok(Uuid::max())
+        Ok(res)
     }
 
-    pub async fn handle_build_order(&self, session_id: Uuid, orders_str: &str) -> Result<Uuid, String> {
-        let orders: Vec<MappedBuildOrder> = serde_json::from_str(orders_str)
-            .map_err(|e| format!("Failed to convert {} into json, {}", orders_str, e))?;
+    pub async fn handle_build_order(&self, session_id: Uuid, game: Option<Uuid>, orders: Vec<MappedBuildOrder>) -> Result<OrderOutcome, ErrorResponse> {
         println!("[DEBUG] Orders parsed {:?}", orders);
 
         // Now that it is finalized, we get the session 
-        let mut session_store = self.session_store.write().await;
-        let user_session = session_store.get_mut(&session_id).expect("Something has gone wrong and the session is not found");
-        
-        // Sanity check
-        assert!(user_session.current_game.is_some());
+        let user_session = self.session_for_game(&session_id, game).await?;
         let res = self.order_service
             .send_build_order(&user_session, orders)
+            .await?;
+
+        if let OrderOutcome::Accepted = res {
+            println!("Correctly added the order!");
+        }
+        if let Some(game_id) = user_session.current_game {
+            self.persist_game(&game_id).await;
+        }
+        Ok(res)
+    }
+
+    pub async fn handle_ready(&self, session_id: Uuid, ready: bool) -> Result<OrderOutcome, ErrorResponse> {
+        let user_session = self.session(&session_id).await?;
+        let res = self.order_service
+            .set_ready(&user_session, ready)
+            .await?;
+
+        if let Some(game_id) = user_session.current_game {
+            self.persist_game(&game_id).await;
+        }
+        Ok(res)
+    }
+
+    pub async fn handle_draw(&self, session_id: Uuid, draw: bool) -> Result<OrderOutcome, ErrorResponse> {
+        let user_session = self.session(&session_id).await?;
+        let res = self.order_service
+            .set_draw_vote(&user_session, draw)
+            .await?;
+
+        if let Some(game_id) = user_session.current_game {
+            self.persist_game(&game_id).await;
+        }
+        Ok(res)
+    }
+
+    /// Leaves `game`, or the current game, before it starts. Returns the game left and the
+    /// session's current game afterwards
+    pub async fn handle_leave(&self, session_id: Uuid, game: Option<Uuid>) -> Result<(Uuid, Option<Uuid>), ErrorResponse> {
+        let mut user_session = self.session(&session_id).await?;
+        let game_id = game
+            .or(user_session.current_game)
+            .ok_or(ErrorResponse::new(ErrorCode::NotInGame, "You have not joined a game"))?;
+        self.game_service.leave_game(&game_id, user_session.user).await?;
+        self.persist_game(&game_id).await;
+
+        if user_session.current_game == Some(game_id) {
+            user_session.current_game = self.game_service.last_selected_game(user_session.user).await.map_err(database_error)?;
+            self.session_store.write().await.set_current_game(&session_id, user_session.current_game).await?;
+        }
+        Ok((game_id, user_session.current_game))
+    }
+
+    /// Makes another of the user's games the current one
+    pub async fn handle_switch(&self, session_id: Uuid, game_id: Uuid) -> Result<Uuid, ErrorResponse> {
+        let mut user_session = self.session(&session_id).await?;
+        if !self.game_service.select_game(&game_id, user_session.user).await.map_err(database_error)? {
+            return Err(ErrorResponse::new(ErrorCode::NotInGame, "You are not in that game"));
+        }
+        self.stop_spectating(&user_session).await;
+        user_session.current_game = Some(game_id);
+        self.session_store.write().await.set_current_game(&session_id, user_session.current_game).await?;
+        Ok(game_id)
+    }
+
+    /// Makes a game the session's current one without playing in it
+    pub async fn handle_spectate(&self, session_id: Uuid, game_id: Uuid, password: Option<String>) -> Result<Uuid, ErrorResponse> {
+        let mut user_session = self.session(&session_id).await?;
+        self.game_service.spectate_game(&game_id, user_session.user, password.as_deref()).await?;
+
+        if user_session.current_game != Some(game_id) {
+            self.stop_spectating(&user_session).await;
+        }
+        user_session.current_game = Some(game_id);
+        self.session_store.write().await.set_current_game(&session_id, user_session.current_game).await?;
+        self.persist_game(&game_id).await;
+        Ok(game_id)
+    }
+
+    pub async fn handle_preferences(&self, session_id: Uuid, nations: Vec<Nation>) -> Result<(), ErrorResponse> {
+        let user_session = self.session(&session_id).await?;
+        self.game_service.set_preferences(&user_session, nations).await?;
+
+        if let Some(game_id) = user_session.current_game {
+            self.persist_game(&game_id).await;
+        }
+        Ok(())
+    }
+
+    pub async fn handle_start(&self, session_id: Uuid) -> Result<Uuid, ErrorResponse> {
+        let user_session = self.session(&session_id).await?;
+        let game_id = self.game_service.start_game(&user_session).await?;
+        self.persist_game(&game_id).await;
+        Ok(game_id)
+    }
+
+    pub async fn handle_missing_orders(&self, session_id: Uuid) -> Result<Vec<(UnitType, RegionKey)>, ErrorResponse> {
+        let user_session = self.session(&session_id).await?;
+        Ok(self.order_service.units_without_orders(&user_session).await?)
+    }
+
+    pub async fn handle_context(&self, session_id: Uuid, game: Option<Uuid>) -> Result<GameContext, ErrorResponse>{
+        let user_session = self.session_for_game(&session_id, game).await?;
+
+        self.game_service.get_game_state(&user_session).await
+    }
+
+    pub async fn handle_list_games(&self, session_id: Uuid, filter: GameFilter) -> Result<Vec<GameSummary>, ErrorResponse> {
+        let user_session = self.session(&session_id).await?;
+        Ok(self.game_service.list_games(&user_session, filter).await)
+    }
+
+    /// Subscribes the connection to the events of the session's current game
+    pub async fn handle_watch(&self, connection_id: ConnectionId, session_id: Uuid) -> Result<Uuid, ErrorResponse> {
+        let game_id = self.session(&session_id).await?
+            .current_game
+            .ok_or(ErrorResponse::new(ErrorCode::NotInGame, "You have not joined a game"))?;
+
+        let events = self.game_service
+            .watch_game(&game_id)
             .await
-            .map_err(|e| format!("Failed to submit retreat order: {:?}", e))?;
+            .ok_or(ErrorResponse::new(ErrorCode::GameNotFound, "No game found"))?;
+
+        let mut connections = self.connections.write().await;
+        let handle = connections
+            .get_mut(&connection_id)
+            .ok_or(ErrorResponse::new(ErrorCode::Internal, "The connection has closed"))?;
+        handle.watch(events);
+        Ok(game_id)
+    }
+
+    pub async fn handle_history(&self, session_id: Uuid, phase: Time) -> Result<PhaseHistory, ErrorResponse> {
+        let user_session = self.session(&session_id).await?;
+        let game_id = user_session
+            .current_game
+            .ok_or(ErrorResponse::new(ErrorCode::NotInGame, "You have not joined a game"))?;
+
+        self.order_service
+            .phase_history(game_id, &phase)
+            .await
+            .map_err(database_error)
+    }
 
-        match res {
-            OrderOutcome::Accepted => {println!("Correctly added the order!")}
+    /// Dispatches a single decoded request from a connection and builds the response to send back
+    pub async fn handle_request(&self, connection_id: ConnectionId, request: Request) -> Response {
+        // Only a session that checks out is bound to the connection
+        if let Some(session_id) = request.session() {
+            if let Err(e) = self.refresh_session(&session_id).await {
+                return Response::Error(e);
+            }
+            self.bind_session(&connection_id, Some(session_id)).await;
+        }
+
+        let response = self.dispatch(connection_id, request).await;
+        match &response {
+            Response::Session { token, .. } => self.bind_session(&connection_id, Some(*token)).await,
+            Response::LoggedOut => self.bind_session(&connection_id, None).await,
             _ => {}
         }
-        Ok(Uuid::max())
+        response
     }
 
-    pub async fn handle_context(&self, session_id: Uuid) -> Result<GameContext, String>{
-        // Adds the user to the game on the the session
-        let session_store = self.session_store.read().await;
-        let user_session = session_store.get(&session_id).expect("Something has gone wrong and the session is not found");
-
-        self.game_service.get_game_state(user_session).await
+    async fn dispatch(&self, connection_id: ConnectionId, request: Request) -> Response {
+        match request {
+            Request::Login { username, password } => {
+                match self.handle_login(username, password).await {
+                    Ok(Some((token, user))) => Response::Session { token, user },
+                    Ok(None) => Response::Error(ErrorResponse::new(ErrorCode::InvalidCredentials, "Invalid username or password")),
+                    Err(e) => Response::Error(e),
+                }
+            }
+            Request::Register { username, password } => {
+                match self.handle_registration(username, password).await {
+                    Ok((token, user)) => Response::Session { token, user },
+                    Err(e) => Response::Error(e),
+                }
+            }
+            Request::Logout { session } => {
+                match self.handle_logout(session).await {
+                    Ok(()) => Response::LoggedOut,
+                    Err(e) => Response::Error(e),
+                }
+            }
+            Request::Join { session, game, password } => {
+                match self.handle_join(game, session, password).await {
+                    Ok(game) => Response::Joined { game },
+                    Err(e) => Response::Error(e),
+                }
+            }
+            Request::Create { session, settings } => {
+                match self.handle_create(session, settings).await {
+                    Ok(game) => Response::Created { game },
+                    Err(e) => Response::Error(e),
+                }
+            }
+            Request::Order { session, orders, game } => {
+                let res = match orders {
+                    OrderSet::Main(orders) => self.handle_main_order(session, game, orders).await,
+                    OrderSet::Retreat(orders) => self.handle_retreat_order(session, game, orders).await,
+                    OrderSet::Build(orders) => self.handle_build_order(session, game, orders).await,
+                };
+                match res {
+                    Ok(OrderOutcome::Accepted) => Response::OrdersAccepted,
+                    Ok(OrderOutcome::GameAdvanced) => Response::PhaseAdvanced,
+                    Ok(OrderOutcome::GameFinished(result)) => Response::GameFinished { result },
+                    Err(e) => Response::Error(e),
+                }
+            }
+            Request::Ready { session, ready } => {
+                match self.handle_ready(session, ready).await {
+                    Ok(OrderOutcome::Accepted) => Response::ReadyChanged { ready },
+                    Ok(OrderOutcome::GameAdvanced) => Response::PhaseAdvanced,
+                    Ok(OrderOutcome::GameFinished(result)) => Response::GameFinished { result },
+                    Err(e) => Response::Error(e),
+                }
+            }
+            Request::Draw { session, draw } => {
+                match self.handle_draw(session, draw).await {
+                    Ok(OrderOutcome::GameFinished(result)) => Response::GameFinished { result },
+                    Ok(_) => Response::DrawVoteChanged { draw },
+                    Err(e) => Response::Error(e),
+                }
+            }
+            Request::Leave { session, game } => {
+                match self.handle_leave(session, game).await {
+                    Ok((game, current)) => Response::Left { game, current },
+                    Err(e) => Response::Error(e),
+                }
+            }
+            Request::Switch { session, game } => {
+                match self.handle_switch(session, game).await {
+                    Ok(game) => Response::Switched { game },
+                    Err(e) => Response::Error(e),
+                }
+            }
+            Request::Spectate { session, game, password } => {
+                match self.handle_spectate(session, game, password).await {
+                    Ok(game) => Response::Spectating { game },
+                    Err(e) => Response::Error(e),
+                }
+            }
+            Request::Preferences { session, nations } => {
+                match self.handle_preferences(session, nations).await {
+                    Ok(()) => Response::PreferencesSaved,
+                    Err(e) => Response::Error(e),
+                }
+            }
+            Request::Start { session } => {
+                match self.handle_start(session).await {
+                    Ok(game) => Response::Started { game },
+                    Err(e) => Response::Error(e),
+                }
+            }
+            Request::MissingOrders { session } => {
+                match self.handle_missing_orders(session).await {
+                    Ok(units) => Response::MissingOrders { units },
+                    Err(e) => Response::Error(e),
+                }
+            }
+            Request::Context { session, game } => {
+                match self.handle_context(session, game).await {
+                    Ok(context) => Response::Context(Box::new(context)),
+                    Err(e) => Response::Error(e),
+                }
+            }
+            Request::ListGames { session, filter } => {
+                match self.handle_list_games(session, filter).await {
+                    Ok(games) => Response::Games { games },
+                    Err(e) => Response::Error(e),
+                }
+            }
+            Request::History { session, phase } => {
+                match self.handle_history(session, phase).await {
+                    Ok(history) => Response::History(history),
+                    Err(e) => Response::Error(e),
+                }
+            }
+            Request::Watch { session } => {
+                match self.handle_watch(connection_id, session).await {
+                    Ok(game) => Response::Watching { game },
+                    Err(e) => Response::Error(e),
+                }
+            }
+        }
     }
+}
 
-} 
+fn session_unknown() -> ErrorResponse {
+    ErrorResponse::new(ErrorCode::SessionUnknown, "The session is unknown or has expired")
+}
+
+fn database_error(e: DbErr) -> ErrorResponse {
+    eprintln!("[DB_ERROR] {e}");
+    ErrorResponse::new(ErrorCode::Database, "The server could not reach its database")
+}
 
+fn user_info(user: &UserModel) -> UserInfo {
+    UserInfo { id: user.user_uuid, username: user.username.clone() }
+}
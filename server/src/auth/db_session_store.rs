@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use sea_orm::ActiveValue::Set;
+use sea_orm::sea_query::Expr;
+use sea_orm::{ActiveModelTrait, ColumnTrait, Condition, EntityTrait, QueryFilter};
+use time::OffsetDateTime;
+
+use crate::auth::session::{GameId, Session, SessionId, SessionStore, SessionStoreError, SessionTtl, UserId};
+use crate::data::connection_pool::ConnectionPool;
+use crate::data::session::{ActiveModel, Column, Entity as SessionEntity, Model};
+
+/// Keeps sessions in the `sessions` table so players stay logged in across restarts
+pub struct PostgresSessionStore {
+    connection_pool: Arc<ConnectionPool>,
+}
+
+impl PostgresSessionStore {
+    pub fn new(connection_pool: Arc<ConnectionPool>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+impl From<Model> for Session {
+    fn from(model: Model) -> Self {
+        Session {
+            user: model.user_uuid,
+            current_game: model.current_game,
+            created_at: model.created_at,
+            last_seen: model.last_seen,
+        }
+    }
+}
+
+fn to_active_model(session_id: &SessionId, session: &Session) -> ActiveModel {
+    ActiveModel {
+        session_id: Set(*session_id),
+        user_uuid: Set(session.user),
+        current_game: Set(session.current_game),
+        created_at: Set(session.created_at),
+        last_seen: Set(session.last_seen),
+    }
+}
+
+#[async_trait]
+impl SessionStore for PostgresSessionStore {
+    async fn create(&mut self, user: UserId) -> Result<SessionId, SessionStoreError> {
+        let session_id = SessionId::new_v4();
+        to_active_model(&session_id, &Session::new(user))
+            .insert(self.connection_pool.get_connection())
+            .await?;
+        Ok(session_id)
+    }
+
+    async fn get(&self, session_id: &SessionId) -> Result<Option<Session>, SessionStoreError> {
+        let model = SessionEntity::find_by_id(*session_id)
+            .one(self.connection_pool.get_connection())
+            .await?;
+        Ok(model.map(Session::from))
+    }
+
+    async fn touch(&mut self, session_id: &SessionId, now: OffsetDateTime) -> Result<(), SessionStoreError> {
+        SessionEntity::update_many()
+            .col_expr(Column::LastSeen, Expr::value(now))
+            .filter(Column::SessionId.eq(*session_id))
+            .exec(self.connection_pool.get_connection())
+            .await?;
+        Ok(())
+    }
+
+    async fn set_current_game(&mut self, session_id: &SessionId, game: Option<GameId>) -> Result<(), SessionStoreError> {
+        SessionEntity::update_many()
+            .col_expr(Column::CurrentGame, Expr::value(game))
+            .filter(Column::SessionId.eq(*session_id))
+            .exec(self.connection_pool.get_connection())
+            .await?;
+        Ok(())
+    }
+
+    async fn delete(&mut self, session_id: &SessionId) -> Result<Option<Session>, SessionStoreError> {
+        let Some(session) = self.get(session_id).await? else {
+            return Ok(None);
+        };
+        SessionEntity::delete_by_id(*session_id)
+            .exec(self.connection_pool.get_connection())
+            .await?;
+        Ok(Some(session))
+    }
+
+    async fn sweep(&mut self, now: OffsetDateTime, ttl: &SessionTtl) -> Result<Vec<Session>, SessionStoreError> {
+        let conn = self.connection_pool.get_connection();
+        let expired = Condition::any()
+            .add(Column::LastSeen.lt(now - ttl.idle))
+            .add(Column::CreatedAt.lt(now - ttl.absolute));
+        let expired = SessionEntity::find().filter(expired).all(conn).await?;
+        if expired.is_empty() {
+            return Ok(Vec::new());
+        }
+        SessionEntity::delete_many()
+            .filter(Column::SessionId.is_in(expired.iter().map(|model| model.session_id)))
+            .exec(conn)
+            .await?;
+        Ok(expired.into_iter().map(Session::from).collect())
+    }
+}
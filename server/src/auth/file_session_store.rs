@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use time::OffsetDateTime;
+
+use crate::auth::session::{GameId, InMemoryStore, Session, SessionId, SessionStore, SessionStoreError, SessionTtl, UserId};
+
+/// Keeps sessions in memory and writes all of them to a JSON file after every change.
+/// Meant for running locally without a database, it will not cope with many players.
+pub struct FileSessionStore {
+    path: PathBuf,
+    sessions: InMemoryStore,
+}
+
+impl FileSessionStore {
+    /// Loads whatever sessions were saved at `path`, starting empty if there is no file yet
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, SessionStoreError> {
+        let path = path.into();
+        let sessions: HashMap<SessionId, Session> = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(std::io::Error::from)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self { path, sessions: InMemoryStore::with_sessions(sessions) })
+    }
+
+    /// Writes the sessions next to the file and renames them over it, so a crash part way
+    /// through leaves the previous file intact
+    async fn save(&self) -> Result<(), SessionStoreError> {
+        let bytes = serde_json::to_vec(self.sessions.sessions()).map_err(std::io::Error::from)?;
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        tokio::fs::write(&tmp_path, bytes).await?;
+        tokio::fs::rename(&tmp_path, &self.path).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SessionStore for FileSessionStore {
+    async fn create(&mut self, user: UserId) -> Result<SessionId, SessionStoreError> {
+        let session_id = self.sessions.create(user).await?;
+        self.save().await?;
+        Ok(session_id)
+    }
+
+    async fn get(&self, session_id: &SessionId) -> Result<Option<Session>, SessionStoreError> {
+        self.sessions.get(session_id).await
+    }
+
+    async fn touch(&mut self, session_id: &SessionId, now: OffsetDateTime) -> Result<(), SessionStoreError> {
+        self.sessions.touch(session_id, now).await?;
+        self.save().await
+    }
+
+    async fn set_current_game(&mut self, session_id: &SessionId, game: Option<GameId>) -> Result<(), SessionStoreError> {
+        self.sessions.set_current_game(session_id, game).await?;
+        self.save().await
+    }
+
+    async fn delete(&mut self, session_id: &SessionId) -> Result<Option<Session>, SessionStoreError> {
+        let removed = self.sessions.delete(session_id).await?;
+        if removed.is_some() {
+            self.save().await?;
+        }
+        Ok(removed)
+    }
+
+    async fn sweep(&mut self, now: OffsetDateTime, ttl: &SessionTtl) -> Result<Vec<Session>, SessionStoreError> {
+        let removed = self.sessions.sweep(now, ttl).await?;
+        if !removed.is_empty() {
+            self.save().await?;
+        }
+        Ok(removed)
+    }
+}
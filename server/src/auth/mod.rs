@@ -1,2 +1,4 @@
 pub mod connections_manager;
-pub mod session;
\ No newline at end of file
+pub mod session;
+pub mod db_session_store;
+pub mod file_session_store;
@@ -1,4 +1,7 @@
 use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// I need to be able to create session for users. 
 /// 
@@ -7,33 +10,117 @@ use std::collections::HashMap;
 ///     When the user disconnects from a game or connects to a game that is the game being dicussed
 /// 
 /// 
+use async_trait::async_trait;
+use common::protocol::{ErrorCode, ErrorResponse};
+use sea_orm::DbErr;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use once_cell::sync::Lazy;
+use time::OffsetDateTime;
 use tokio::sync::RwLock;
 
 use crate::auth::session;
-type UserId = Uuid;
-type GameId = Uuid;
-type SessionId = Uuid;
+use crate::game::game_service::GameService;
+pub type UserId = Uuid;
+pub type GameId = Uuid;
+pub type SessionId = Uuid;
 
+/// Keeps track of every logged in session. Stores hand out copies of sessions, so changes
+/// go through the methods that set a single field rather than writing a copy back.
+#[async_trait]
 pub trait SessionStore: Send + Sync{
-    fn create(&mut self, user: UserId) -> SessionId;
+    async fn create(&mut self, user: UserId) -> Result<SessionId, SessionStoreError>;
 
-    fn get_mut(&mut self, session_id: &SessionId) -> Option<&mut Session>;
+    async fn get(&self, session_id: &SessionId) -> Result<Option<Session>, SessionStoreError>;
 
-    fn get(&self, session_id: &SessionId) -> Option<&Session>;
+    /// Marks the session as used at `now`
+    async fn touch(&mut self, session_id: &SessionId, now: OffsetDateTime) -> Result<(), SessionStoreError>;
+
+    async fn set_current_game(&mut self, session_id: &SessionId, game: Option<GameId>) -> Result<(), SessionStoreError>;
     
-    fn delete(&mut self, session: &SessionId) -> Option<Session>;
+    async fn delete(&mut self, session: &SessionId) -> Result<Option<Session>, SessionStoreError>;
+
+    /// Removes every session that has expired by `now`, returning the removed sessions
+    async fn sweep(&mut self, now: OffsetDateTime, ttl: &SessionTtl) -> Result<Vec<Session>, SessionStoreError>;
+}
+
+#[derive(Debug)]
+pub enum SessionStoreError {
+    Database(DbErr),
+    File(std::io::Error),
+}
+
+impl fmt::Display for SessionStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionStoreError::Database(e) => write!(f, "session database error: {e}"),
+            SessionStoreError::File(e) => write!(f, "session file error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SessionStoreError {}
+
+impl From<DbErr> for SessionStoreError {
+    fn from(e: DbErr) -> Self {
+        SessionStoreError::Database(e)
+    }
+}
+
+impl From<std::io::Error> for SessionStoreError {
+    fn from(e: std::io::Error) -> Self {
+        SessionStoreError::File(e)
+    }
+}
+
+impl From<SessionStoreError> for ErrorResponse {
+    fn from(e: SessionStoreError) -> Self {
+        eprintln!("[SESSION_ERROR] {e}");
+        ErrorResponse::new(ErrorCode::Database, "The server could not reach its session store")
+    }
 }
 
 pub struct InMemoryStore {
     sessions: HashMap<SessionId, Session>
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Session {
     pub user: UserId,
     pub current_game: Option<GameId>,
+    #[serde(with = "time::serde::timestamp")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::timestamp")]
+    pub last_seen: OffsetDateTime,
+}
+
+impl Session {
+    pub fn new(user: UserId) -> Self {
+        let now = OffsetDateTime::now_utc();
+        Self { user, current_game: None, created_at: now, last_seen: now }
+    }
+
+    /// Whether `last_seen` is stale enough to be worth writing back. Refreshing it on every
+    /// request would cost a store write per request for no real gain in precision
+    pub fn needs_touch(&self, now: OffsetDateTime) -> bool {
+        now - self.last_seen > LAST_SEEN_PRECISION
+    }
+
+    /// A session expires once it has gone unused for too long, or has simply been around too long
+    pub fn is_expired(&self, now: OffsetDateTime, ttl: &SessionTtl) -> bool {
+        now - self.last_seen > ttl.idle || now - self.created_at > ttl.absolute
+    }
+}
+
+/// How far behind the last request a session's `last_seen` may lag
+const LAST_SEEN_PRECISION: Duration = Duration::from_secs(30);
+
+/// How long sessions are allowed to live
+#[derive(Debug, Clone, Copy)]
+pub struct SessionTtl {
+    /// Time allowed between requests
+    pub idle: Duration,
+    /// Time allowed since login, however active the session is
+    pub absolute: Duration,
 }
 
 impl InMemoryStore {
@@ -42,28 +129,102 @@ impl InMemoryStore {
     }
 }
 
+impl InMemoryStore {
+    /// Starts from sessions that were saved somewhere else
+    pub fn with_sessions(sessions: HashMap<SessionId, Session>) -> Self {
+        Self { sessions }
+    }
+
+    pub fn sessions(&self) -> &HashMap<SessionId, Session> {
+        &self.sessions
+    }
+}
+
+#[async_trait]
 impl SessionStore for InMemoryStore {
-    fn create(&mut self, user: UserId) -> SessionId {
+    async fn create(&mut self, user: UserId) -> Result<SessionId, SessionStoreError> {
         let session_id = SessionId::new_v4();
         self.sessions.insert(
             session_id,
-            Session { user, current_game: None }
+            Session::new(user)
         );
-        session_id
+        Ok(session_id)
+    }
+
+    async fn get(&self, session_id: &SessionId) -> Result<Option<Session>, SessionStoreError> {
+        Ok(self.sessions.get(session_id).cloned())
     }
 
-    fn get_mut(&mut self, session_id: &SessionId) -> Option<&mut Session> {
-        self.sessions.get_mut(session_id)
+    async fn touch(&mut self, session_id: &SessionId, now: OffsetDateTime) -> Result<(), SessionStoreError> {
+        if let Some(session) = self.sessions.get_mut(session_id) {
+            session.last_seen = now;
+        }
+        Ok(())
     }
 
-    fn get(&self, session_id: &SessionId) -> Option<&Session> {
-        self.sessions.get(session_id)
+    async fn set_current_game(&mut self, session_id: &SessionId, game: Option<GameId>) -> Result<(), SessionStoreError> {
+        if let Some(session) = self.sessions.get_mut(session_id) {
+            session.current_game = game;
+        }
+        Ok(())
     }
 
-    fn delete(&mut self, session_id: &SessionId) -> Option<Session> {
-        self.sessions.remove(session_id)
+    async fn delete(&mut self, session_id: &SessionId) -> Result<Option<Session>, SessionStoreError> {
+        Ok(self.sessions.remove(session_id))
+    }
+
+    async fn sweep(&mut self, now: OffsetDateTime, ttl: &SessionTtl) -> Result<Vec<Session>, SessionStoreError> {
+        let expired: Vec<SessionId> = self
+            .sessions
+            .iter()
+            .filter(|(_, session)| session.is_expired(now, ttl))
+            .map(|(session_id, _)| *session_id)
+            .collect();
+        Ok(expired.iter().filter_map(|session_id| self.sessions.remove(session_id)).collect())
     }
 }
 
+/// Periodically clears expired sessions so abandoned logins do not pile up, along with
+/// whatever game they were spectating
+pub fn spawn_sweeper(store: Arc<RwLock<dyn SessionStore>>, game_service: Arc<GameService>, ttl: SessionTtl, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let swept = store.write().await.sweep(OffsetDateTime::now_utc(), &ttl).await;
+            match swept {
+                Ok(expired) => {
+                    for session in &expired {
+                        game_service.forget_spectator(session).await;
+                    }
+                }
+                Err(e) => eprintln!("[SESSION_ERROR] Failed to sweep sessions: {e}"),
+            }
+        }
+    });
+}
 
-pub static SESSION_STORE: Lazy<RwLock<InMemoryStore>> = Lazy::new(|| RwLock::new(InMemoryStore::new()));
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TTL: SessionTtl = SessionTtl { idle: Duration::from_secs(60), absolute: Duration::from_secs(60 * 60) };
+
+    #[tokio::test]
+    async fn sweeping_hands_back_the_expired_sessions() {
+        let now = OffsetDateTime::now_utc();
+        let mut stale = Session::new(Uuid::new_v4());
+        stale.current_game = Some(Uuid::new_v4());
+        stale.last_seen = now - Duration::from_secs(61);
+        let fresh = Session::new(Uuid::new_v4());
+        let (stale_id, fresh_id) = (SessionId::new_v4(), SessionId::new_v4());
+        let mut store = InMemoryStore::with_sessions(HashMap::from([(stale_id, stale.clone()), (fresh_id, fresh)]));
+
+        let swept = store.sweep(now, &TTL).await.unwrap();
+        assert_eq!(swept.len(), 1);
+        assert_eq!(swept[0].user, stale.user);
+        assert_eq!(swept[0].current_game, stale.current_game);
+        assert!(store.get(&stale_id).await.unwrap().is_none());
+        assert!(store.get(&fresh_id).await.unwrap().is_some());
+    }
+}
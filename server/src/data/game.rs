@@ -1,3 +1,4 @@
+use diplomacy::{Phase, Season, Time};
 use sea_orm::prelude::*;
 
 #[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
@@ -15,6 +16,18 @@ pub enum GamePhase {
     WinterBuild,
 }
 
+impl From<&Time> for GamePhase {
+    fn from(time: &Time) -> Self {
+        match (time.season(), time.phase()) {
+            (Season::Spring, Phase::Main) => GamePhase::SpringMovement,
+            (Season::Spring | Season::Summer, _) => GamePhase::SpringRetreat,
+            (Season::Fall, Phase::Main) => GamePhase::FallMovement,
+            (Season::Fall, _) => GamePhase::FallRetreat,
+            (Season::Winter, _) => GamePhase::WinterBuild,
+        }
+    }
+}
+
 // TODO: This should be primary key of the name 
 
 #[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel)]
@@ -22,10 +35,18 @@ pub enum GamePhase {
 pub struct Model {
     #[sea_orm(primary_key)]
     pub game_id: i32,
+    #[sea_orm(unique)]
+    pub game_uuid: Option<Uuid>,
     pub name: String,
     pub year: i32,
     pub game_phase: GamePhase, 
+    /// The whole game as a `GameSnapshot`, rewritten every time the game changes
+    pub state: Option<Json>,
     pub created_at: time::PrimitiveDateTime,
+    /// When the game was won or drawn, `None` while it is still being played
+    pub finished_at: Option<TimeDateTimeWithTimeZone>,
+    /// The `GameResult` the game ended with
+    pub result: Option<Json>,
 }
 
 #[derive(Debug, Clone, EnumIter, DeriveRelation)]
@@ -0,0 +1,23 @@
+use sea_orm::prelude::*;
+
+/// A user taking part in a game, one row for each game they are in
+#[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "game_members")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub game_uuid: Uuid,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub user_uuid: Uuid,
+    pub joined_at: TimeDateTimeWithTimeZone,
+    /// The last time the user made this their active game, the newest one is picked at login
+    pub selected_at: TimeDateTimeWithTimeZone,
+}
+
+#[derive(Debug, Clone, EnumIter, DeriveRelation)]
+pub enum Relation {
+
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    
+}
@@ -0,0 +1,31 @@
+use sea_orm::prelude::*;
+
+/// What a game was created with, one row per game
+#[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "game_settings")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub game_uuid: Uuid,
+    pub name: String,
+    /// e.g. `standard`
+    pub variant: String,
+    /// `full`, `public` or `gunboat`
+    pub press: String,
+    pub movement_deadline_secs: i64,
+    pub retreat_deadline_secs: i64,
+    pub build_deadline_secs: i64,
+    pub min_players: i32,
+    pub max_players: i32,
+    /// Argon2 hash of the password or invite code, `None` for open games
+    pub password_hash: Option<String>,
+    pub created_at: TimeDateTimeWithTimeZone,
+}
+
+#[derive(Debug, Clone, EnumIter, DeriveRelation)]
+pub enum Relation {
+
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    
+}
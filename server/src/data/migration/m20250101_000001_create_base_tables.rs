@@ -0,0 +1,126 @@
+use sea_orm_migration::prelude::*;
+
+use super::create_enum_if_missing;
+
+/// The tables that used to be made by hand from `schema.sql`
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+pub enum Users {
+    Table,
+    UserId,
+    Username,
+    PasswordHash,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+pub enum Games {
+    Table,
+    GameId,
+    Name,
+    Year,
+    GamePhase,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum PlayersInGame {
+    Table,
+    PlayerInGameId,
+    GameId,
+    UserId,
+    Nation,
+    Status,
+    JoinedAt,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        create_enum_if_missing(
+            manager,
+            "game_phase",
+            &["SpringMovement", "SpringRetreat", "FallMovement", "FallRetreat", "WinterBuild"],
+        ).await?;
+        create_enum_if_missing(manager, "player_status", &["active", "eliminated", "won", "spectator"]).await?;
+        create_enum_if_missing(
+            manager,
+            "nation",
+            &["England", "France", "Germany", "Italy", "Austria", "Russia", "Turkey"],
+        ).await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Users::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Users::UserId).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(Users::Username).string_len(255))
+                    .col(ColumnDef::new(Users::PasswordHash).text().not_null())
+                    .col(ColumnDef::new(Users::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Games::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Games::GameId).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(Games::Name).string_len(255))
+                    .col(ColumnDef::new(Games::Year).integer().not_null())
+                    .col(ColumnDef::new(Games::GamePhase).custom(Alias::new("game_phase")).not_null())
+                    .col(ColumnDef::new(Games::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(PlayersInGame::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(PlayersInGame::PlayerInGameId).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(PlayersInGame::GameId).integer().not_null())
+                    .col(ColumnDef::new(PlayersInGame::UserId).integer().not_null())
+                    .col(ColumnDef::new(PlayersInGame::Nation).custom(Alias::new("nation")).not_null())
+                    .col(
+                        ColumnDef::new(PlayersInGame::Status)
+                            .custom(Alias::new("player_status"))
+                            .not_null()
+                            .default(Expr::cust("'active'")),
+                    )
+                    .col(ColumnDef::new(PlayersInGame::JoinedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(PlayersInGame::Table, PlayersInGame::GameId)
+                            .to(Games::Table, Games::GameId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(PlayersInGame::Table, PlayersInGame::UserId)
+                            .to(Users::Table, Users::UserId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .index(Index::create().col(PlayersInGame::GameId).col(PlayersInGame::UserId).unique())
+                    .index(Index::create().col(PlayersInGame::GameId).col(PlayersInGame::Nation).unique())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(PlayersInGame::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(Games::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(Users::Table).to_owned()).await?;
+        manager
+            .get_connection()
+            .execute_unprepared("DROP TYPE IF EXISTS nation, player_status, game_phase")
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20250101_000001_create_base_tables::Users;
+
+/// Gives users the id that sessions and games know them by
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+enum UsersUuid {
+    UserUuid,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(UsersUuid::UserUuid)
+                            .uuid()
+                            .not_null()
+                            .unique_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(Table::alter().table(Users::Table).drop_column(UsersUuid::UserUuid).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,46 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20250101_000001_create_base_tables::Users;
+
+/// Backs `PostgresSessionStore`
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+enum Sessions {
+    Table,
+    SessionId,
+    UserUuid,
+    CurrentGame,
+    CreatedAt,
+    LastSeen,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Sessions::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Sessions::SessionId).uuid().not_null().primary_key())
+                    .col(ColumnDef::new(Sessions::UserUuid).uuid().not_null())
+                    .col(ColumnDef::new(Sessions::CurrentGame).uuid())
+                    .col(ColumnDef::new(Sessions::CreatedAt).timestamp_with_time_zone().not_null())
+                    .col(ColumnDef::new(Sessions::LastSeen).timestamp_with_time_zone().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Sessions::Table, Sessions::UserUuid)
+                            .to(Users::Table, Alias::new("user_uuid"))
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(Sessions::Table).to_owned()).await
+    }
+}
@@ -0,0 +1,40 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20250101_000001_create_base_tables::Games;
+
+/// Lets a running game be saved and restored, see `GameSnapshot`
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+enum GamesState {
+    GameUuid,
+    State,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Games::Table)
+                    .add_column_if_not_exists(ColumnDef::new(GamesState::GameUuid).uuid().unique_key())
+                    .add_column_if_not_exists(ColumnDef::new(GamesState::State).json_binary())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Games::Table)
+                    .drop_column(GamesState::State)
+                    .drop_column(GamesState::GameUuid)
+                    .to_owned(),
+            )
+            .await
+    }
+}
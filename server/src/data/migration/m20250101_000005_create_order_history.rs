@@ -0,0 +1,103 @@
+use sea_orm_migration::prelude::*;
+
+/// The archive of every accepted order set and every adjudicated order
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+enum OrderSubmissions {
+    Table,
+    SubmissionId,
+    GameUuid,
+    UserUuid,
+    Nation,
+    Phase,
+    Orders,
+    SubmittedAt,
+}
+
+#[derive(DeriveIden)]
+enum OrderResults {
+    Table,
+    ResultId,
+    GameUuid,
+    Phase,
+    OrderText,
+    Succeeded,
+    Outcome,
+    ResolvedAt,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(OrderSubmissions::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(OrderSubmissions::SubmissionId).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(OrderSubmissions::GameUuid).uuid().not_null())
+                    .col(ColumnDef::new(OrderSubmissions::UserUuid).uuid().not_null())
+                    .col(ColumnDef::new(OrderSubmissions::Nation).string_len(3).not_null())
+                    .col(ColumnDef::new(OrderSubmissions::Phase).string_len(16).not_null())
+                    .col(ColumnDef::new(OrderSubmissions::Orders).json_binary().not_null())
+                    .col(
+                        ColumnDef::new(OrderSubmissions::SubmittedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("order_submissions_phase")
+                    .if_not_exists()
+                    .table(OrderSubmissions::Table)
+                    .col(OrderSubmissions::GameUuid)
+                    .col(OrderSubmissions::Phase)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(OrderResults::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(OrderResults::ResultId).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(OrderResults::GameUuid).uuid().not_null())
+                    .col(ColumnDef::new(OrderResults::Phase).string_len(16).not_null())
+                    .col(ColumnDef::new(OrderResults::OrderText).text().not_null())
+                    .col(ColumnDef::new(OrderResults::Succeeded).boolean().not_null())
+                    .col(ColumnDef::new(OrderResults::Outcome).text().not_null())
+                    .col(
+                        ColumnDef::new(OrderResults::ResolvedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("order_results_phase")
+                    .if_not_exists()
+                    .table(OrderResults::Table)
+                    .col(OrderResults::GameUuid)
+                    .col(OrderResults::Phase)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(OrderResults::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(OrderSubmissions::Table).to_owned()).await
+    }
+}
@@ -0,0 +1,40 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20250101_000001_create_base_tables::Games;
+
+/// Records when and how a game ended so finished games can be told apart without reading `state`
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+enum GamesResult {
+    FinishedAt,
+    Result,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Games::Table)
+                    .add_column_if_not_exists(ColumnDef::new(GamesResult::FinishedAt).timestamp_with_time_zone())
+                    .add_column_if_not_exists(ColumnDef::new(GamesResult::Result).json_binary())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Games::Table)
+                    .drop_column(GamesResult::Result)
+                    .drop_column(GamesResult::FinishedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
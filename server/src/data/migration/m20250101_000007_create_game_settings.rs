@@ -0,0 +1,57 @@
+use sea_orm_migration::prelude::*;
+
+/// Keeps what each game was created with, see `GameSettings`
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+enum GameSettings {
+    Table,
+    GameUuid,
+    Name,
+    Variant,
+    Press,
+    MovementDeadlineSecs,
+    RetreatDeadlineSecs,
+    BuildDeadlineSecs,
+    MinPlayers,
+    MaxPlayers,
+    PasswordHash,
+    CreatedAt,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(GameSettings::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(GameSettings::GameUuid).uuid().not_null().primary_key())
+                    .col(ColumnDef::new(GameSettings::Name).string_len(255).not_null())
+                    .col(ColumnDef::new(GameSettings::Variant).string_len(32).not_null())
+                    .col(ColumnDef::new(GameSettings::Press).string_len(16).not_null())
+                    .col(ColumnDef::new(GameSettings::MovementDeadlineSecs).big_integer().not_null())
+                    .col(ColumnDef::new(GameSettings::RetreatDeadlineSecs).big_integer().not_null())
+                    .col(ColumnDef::new(GameSettings::BuildDeadlineSecs).big_integer().not_null())
+                    .col(ColumnDef::new(GameSettings::MinPlayers).integer().not_null())
+                    .col(ColumnDef::new(GameSettings::MaxPlayers).integer().not_null())
+                    .col(ColumnDef::new(GameSettings::PasswordHash).string_len(255))
+                    .col(
+                        ColumnDef::new(GameSettings::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(GameSettings::Table).to_owned())
+            .await
+    }
+}
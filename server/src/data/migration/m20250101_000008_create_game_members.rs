@@ -0,0 +1,60 @@
+use sea_orm_migration::prelude::*;
+
+/// Which users are in which games, so a user can be in several at once and get back to
+/// them after logging in again
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+enum GameMembers {
+    Table,
+    GameUuid,
+    UserUuid,
+    JoinedAt,
+    SelectedAt,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(GameMembers::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(GameMembers::GameUuid).uuid().not_null())
+                    .col(ColumnDef::new(GameMembers::UserUuid).uuid().not_null())
+                    .col(
+                        ColumnDef::new(GameMembers::JoinedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(GameMembers::SelectedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .primary_key(Index::create().col(GameMembers::GameUuid).col(GameMembers::UserUuid))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("game_members_user")
+                    .if_not_exists()
+                    .table(GameMembers::Table)
+                    .col(GameMembers::UserUuid)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(GameMembers::Table).to_owned())
+            .await
+    }
+}
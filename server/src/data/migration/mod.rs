@@ -0,0 +1,47 @@
+//! Every change to the database schema, applied in order on boot or with `server migrate`.
+//!
+//! Each migration only creates what is missing so databases that were set up by hand
+//! from the old `schema.sql` can be brought under migrations without being wiped.
+
+use sea_orm_migration::prelude::*;
+
+mod m20250101_000001_create_base_tables;
+mod m20250101_000002_add_user_uuid;
+mod m20250101_000003_create_sessions;
+mod m20250101_000004_add_game_state;
+mod m20250101_000005_create_order_history;
+mod m20250101_000006_add_game_result;
+mod m20250101_000007_create_game_settings;
+mod m20250101_000008_create_game_members;
+
+pub struct Migrator;
+
+#[async_trait::async_trait]
+impl MigratorTrait for Migrator {
+    fn migrations() -> Vec<Box<dyn MigrationTrait>> {
+        vec![
+            Box::new(m20250101_000001_create_base_tables::Migration),
+            Box::new(m20250101_000002_add_user_uuid::Migration),
+            Box::new(m20250101_000003_create_sessions::Migration),
+            Box::new(m20250101_000004_add_game_state::Migration),
+            Box::new(m20250101_000005_create_order_history::Migration),
+            Box::new(m20250101_000006_add_game_result::Migration),
+            Box::new(m20250101_000007_create_game_settings::Migration),
+            Box::new(m20250101_000008_create_game_members::Migration),
+        ]
+    }
+}
+
+/// Postgres has no `CREATE TYPE IF NOT EXISTS`, so swallow the duplicate instead
+async fn create_enum_if_missing(manager: &SchemaManager<'_>, name: &str, values: &[&str]) -> Result<(), DbErr> {
+    let values = values
+        .iter()
+        .map(|value| format!("'{value}'"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let sql = format!(
+        "DO $$ BEGIN CREATE TYPE {name} AS ENUM ({values}); EXCEPTION WHEN duplicate_object THEN null; END $$;"
+    );
+    manager.get_connection().execute_unprepared(&sql).await?;
+    Ok(())
+}
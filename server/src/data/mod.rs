@@ -2,17 +2,24 @@ use postgres::{Client, NoTls, Error};
 use sea_orm::{ActiveModelTrait, ActiveValue::NotSet, Database, DatabaseConnection, Set};
 pub mod user;
 pub mod game;
+pub mod game_settings;
+pub mod game_member;
+pub mod session;
+pub mod order_submission;
+pub mod order_result;
 
 use user::{ActiveModel as UserModel, Entity as User};
 use common::hash::hash_password;
 use sea_orm::EntityTrait;
 
 pub mod connection_pool;
+pub mod migration;
 
 pub async fn add_user(username: String, password: String, connection: &DatabaseConnection) -> Result<(), sea_orm::DbErr> {
     let hashed_password = hash_password(&password);
     let user_model = UserModel {
         user_id: NotSet,
+        user_uuid: NotSet,
         username: Set(username),
         password_hash: Set(hashed_password),
         created_at: NotSet,
@@ -0,0 +1,27 @@
+use sea_orm::prelude::*;
+
+/// How a single order fared when its phase was adjudicated
+#[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "order_results")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub result_id: i32,
+    pub game_uuid: Uuid,
+    /// The short name of the phase, e.g. `S1901M`
+    pub phase: String,
+    #[sea_orm(column_type = "Text")]
+    pub order_text: String,
+    pub succeeded: bool,
+    #[sea_orm(column_type = "Text")]
+    pub outcome: String,
+    pub resolved_at: TimeDateTimeWithTimeZone,
+}
+
+#[derive(Debug, Clone, EnumIter, DeriveRelation)]
+pub enum Relation {
+
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    
+}
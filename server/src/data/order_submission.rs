@@ -0,0 +1,25 @@
+use sea_orm::prelude::*;
+
+/// One set of orders a player handed in, kept even after the phase resolves
+#[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "order_submissions")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub submission_id: i32,
+    pub game_uuid: Uuid,
+    pub user_uuid: Uuid,
+    pub nation: String,
+    /// The short name of the phase, e.g. `S1901M`
+    pub phase: String,
+    pub orders: Json,
+    pub submitted_at: TimeDateTimeWithTimeZone,
+}
+
+#[derive(Debug, Clone, EnumIter, DeriveRelation)]
+pub enum Relation {
+
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    
+}
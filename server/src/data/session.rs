@@ -0,0 +1,21 @@
+use sea_orm::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "sessions")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub session_id: Uuid,
+    pub user_uuid: Uuid,
+    pub current_game: Option<Uuid>,
+    pub created_at: TimeDateTimeWithTimeZone,
+    pub last_seen: TimeDateTimeWithTimeZone,
+}
+
+#[derive(Debug, Clone, EnumIter, DeriveRelation)]
+pub enum Relation {
+
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    
+}
@@ -0,0 +1,27 @@
+-- Example data for local development, load it once the server has migrated the schema:
+--   docker exec -i postgres psql -U postgres < seed.sql
+
+-- Fake Users
+INSERT INTO users (username, password_hash) VALUES
+  ('alice', 'HASH_PLACEHOLDER'),
+  ('bob',   'HASH_PLACEHOLDER'),
+  ('carol', 'HASH_PLACEHOLDER'),
+  ('dan',   'HASH_PLACEHOLDER'),
+  ('eve',   'HASH_PLACEHOLDER'),
+  ('frank', 'HASH_PLACEHOLDER'),
+  ('grace', 'HASH_PLACEHOLDER');
+
+-- Game
+INSERT INTO games (name, year, game_phase) VALUES
+  ('Europe 1901', 1901, 'SpringMovement');
+
+-- Easiest way alice -> grace 
+INSERT INTO players_in_game (game_id, user_id, nation)
+VALUES
+  (1, 1, 'England'), 
+  (1, 2, 'France'),
+  (1, 3, 'Germany'),
+  (1, 4, 'Italy'),
+  (1, 5, 'Austria'),
+  (1, 6, 'Russia'),
+  (1, 7, 'Turkey');
\ No newline at end of file
@@ -20,7 +20,7 @@ until docker exec "$CONTAINER_NAME" pg_isready -U postgres > /dev/null 2>&1; do
   sleep 1
 done
 
-echo "PostgreSQL is ready. Creating schema..."
+echo "PostgreSQL is ready. Setting up the role..."
 docker exec -i "$CONTAINER_NAME" psql -U postgres --set ON_ERROR_STOP=1 < "$SQL_FILE"
 
-echo "✔ Setup complete!"
+echo "✔ Setup complete! The server creates the tables when it starts (or run: cargo run -p server -- migrate)"
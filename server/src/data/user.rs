@@ -5,6 +5,8 @@ use sea_orm::prelude::*;
 pub struct Model {
     #[sea_orm(primary_key)]
     pub user_id: i32,
+    #[sea_orm(unique)]
+    pub user_uuid: Uuid,
     pub username: String,
     #[sea_orm(column_type = "Text")]
     pub password_hash: String, 
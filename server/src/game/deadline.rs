@@ -0,0 +1,23 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use time::OffsetDateTime;
+
+use crate::game::game_service::GameService;
+use crate::order::order_service::OrderService;
+
+/// Periodically adjudicates every game whose deadline has passed, anyone who was not done
+/// holds, disbands or waives
+pub fn spawn_deadline_timer(order_service: Arc<OrderService>, game_service: Arc<GameService>, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            for game_id in order_service.adjudicate_overdue(OffsetDateTime::now_utc()).await {
+                if let Err(e) = game_service.save_game(&game_id).await {
+                    eprintln!("[DB_ERROR] Failed to save game {game_id} after its deadline: {e}");
+                }
+            }
+        }
+    });
+}
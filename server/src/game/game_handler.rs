@@ -2,106 +2,506 @@ use std::borrow::Cow;
 use std::fmt;
 use std::collections::{HashMap, HashSet};
 
-use diplomacy::{Calendar, Command, Time};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use common::hash::verify_password;
+use common::protocol::{
+    ErrorCode, ErrorResponse, GameEvent, GameResult, GameSummary, NationAssignment, OrderResult, PhaseDeadlines, PressMode,
+};
+use diplomacy::Time;
+use diplomacy::order::{BuildCommand, RetreatCommand};
+use time::OffsetDateTime;
 use uuid::Uuid;
 use diplomacy::{
-    Nation, Phase, Unit, UnitPosition,
+    Nation, Phase, Unit, UnitPosition, UnitType,
     geo::RegionKey,
     judge::{
         MappedBuildOrder, MappedMainOrder, MappedRetreatOrder,
-        Rulebook, Submission,
+        OrderState, Rulebook, Submission, retreat,
     },
     UnitPositions,
 };
 
 use crate::{
     game::game_instance::{GameInstance, PendingRetreat},
+    game::nation_assignment,
     order::order_collector::{
         MainOrderCollector, RetreatOrderCollector, BuildOrderCollector, OrderCollector,
     },
 };
 
-use diplomacy::Season::Spring;
-use diplomacy::Season::Fall;
-use diplomacy::Season::Winter;
-use diplomacy::Phase::{Main,Retreat,Build};
-
 
 type UserId = Uuid;
 
+pub const DEFAULT_CIVIL_DISORDER_AFTER: u32 = 2;
+
 #[derive(Debug)]
 pub enum OrderError {
     WrongPhase,
-    UserReadied,
     IncorrectOrderCount,
     InvalidOrderCount { expected: usize, found: usize },
     InvalidOrderPositions,
-    GameNotFound
+    /// A build somewhere other than an owned, empty home centre or on the wrong terrain
+    InvalidBuildSite,
+    /// Builds from a nation that has to disband this winter, or the other way round
+    WrongAdjustment,
+    GameNotFound,
+    NotInGame,
+    /// The game has been won or drawn, nothing more can be ordered
+    GameFinished,
+    /// Waiting players have no nation to order for yet
+    GameNotStarted,
+    GameStarted,
+    NotHost,
+    NotEnoughPlayers { needed: usize },
+    /// Preferences naming something other than a nation in the game, or one twice
+    UnknownNation,
 }
 
 #[derive(Debug)]
 pub enum OrderOutcome {
     Accepted,
     GameAdvanced,
+    /// Every surviving player agreed to a draw
+    GameFinished(GameResult),
+}
+
+impl From<OrderError> for ErrorResponse {
+    fn from(e: OrderError) -> Self {
+        match e {
+            OrderError::WrongPhase => ErrorResponse::new(ErrorCode::WrongPhase, "These orders are not for the current phase"),
+            OrderError::IncorrectOrderCount => ErrorResponse::new(ErrorCode::InvalidOrders, "The wrong number of orders was submitted"),
+            OrderError::InvalidOrderCount { expected, found } => ErrorResponse::new(
+                ErrorCode::OrderCountMismatch { expected, found },
+                format!("Expected {expected} orders but found {found}"),
+            ),
+            OrderError::InvalidOrderPositions => ErrorResponse::new(ErrorCode::InvalidOrders, "The orders do not match your units"),
+            OrderError::InvalidBuildSite => ErrorResponse::new(
+                ErrorCode::InvalidOrders,
+                "Units can only be built in your own empty home centres, fleets on a coast",
+            ),
+            OrderError::WrongAdjustment => ErrorResponse::new(
+                ErrorCode::InvalidOrders,
+                "Those orders do not match the builds or disbands you are owed this winter",
+            ),
+            OrderError::GameNotFound => ErrorResponse::new(ErrorCode::GameNotFound, "No game found"),
+            OrderError::NotInGame => ErrorResponse::new(ErrorCode::NotInGame, "You have not joined a game"),
+            OrderError::GameFinished => ErrorResponse::new(ErrorCode::GameFinished, "This game is over"),
+            OrderError::GameNotStarted => ErrorResponse::new(
+                ErrorCode::GameNotStarted,
+                "The game has not started, nations are handed out once it does",
+            ),
+            OrderError::GameStarted => ErrorResponse::new(ErrorCode::GameStarted, "The game has already started"),
+            OrderError::NotHost => ErrorResponse::new(ErrorCode::NotHost, "Only the player who created the game can start it"),
+            OrderError::NotEnoughPlayers { needed } => ErrorResponse::new(
+                ErrorCode::NotEnoughPlayers,
+                format!("The game needs {needed} players before it can start"),
+            ),
+            OrderError::UnknownNation => ErrorResponse::new(
+                ErrorCode::InvalidOrders,
+                "Preferences have to list nations in the game, each at most once",
+            ),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
-pub struct JoinError;
+pub enum JoinError {
+    GameNotFound,
+    GameFull,
+    AlreadyJoined,
+    GameFinished,
+    WrongPassword,
+    GameStarted,
+}
 
 impl fmt::Display for JoinError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "This game is full or the user has already joined")
+        match self {
+            JoinError::GameNotFound => write!(f, "No game exists with that id"),
+            JoinError::GameFull => write!(f, "This game is full"),
+            JoinError::AlreadyJoined => write!(f, "The user has already joined this game"),
+            JoinError::GameFinished => write!(f, "This game is over"),
+            JoinError::WrongPassword => write!(f, "This game needs the right password or invite code to join"),
+            JoinError::GameStarted => write!(f, "This game has started and has no abandoned nations to take over"),
+        }
     }
 }
 
 impl std::error::Error for JoinError {}
 
+impl From<JoinError> for ErrorResponse {
+    fn from(e: JoinError) -> Self {
+        let code = match e {
+            JoinError::GameNotFound => ErrorCode::GameNotFound,
+            JoinError::GameFull => ErrorCode::GameFull,
+            JoinError::AlreadyJoined => ErrorCode::AlreadyJoined,
+            JoinError::GameFinished => ErrorCode::GameFinished,
+            JoinError::WrongPassword => ErrorCode::WrongPassword,
+            JoinError::GameStarted => ErrorCode::GameStarted,
+        };
+        ErrorResponse::new(code, e.to_string())
+    }
+}
+
 pub struct GameHandler {
     pub id: Uuid,
     pub instance: GameInstance,
     pub main_orders: MainOrderCollector,
     pub retreat_orders: RetreatOrderCollector,
     pub build_orders: BuildOrderCollector,
+    /// How each order fared in the most recently resolved phase
+    pub last_results: Vec<OrderResult>,
+    pub deadlines: PhaseDeadlines,
+    /// When the current phase is adjudicated regardless, set once the game has started
+    pub deadline: Option<OffsetDateTime>,
+    /// How many deadlines in a row a player can miss before their nation falls into civil disorder
+    pub civil_disorder_after: u32,
+    /// The players who would accept a draw between every surviving nation
+    pub draw_votes: HashSet<UserId>,
+    /// Set once the game is won or drawn, after that nothing can be ordered
+    pub result: Option<GameResult>,
+    /// Shown in the lobby
+    pub name: String,
+    pub press: PressMode,
+    /// Hash of the password or invite code needed to join, `None` for open games
+    pub password_hash: Option<String>,
+    pub assignment: NationAssignment,
+    /// Seeds the draw of nations at the start
+    pub seed: u64,
+    /// The nations each waiting player would like, best first
+    pub preferences: HashMap<UserId, Vec<Nation>>,
+    /// Whoever joined first, the creator, who may start the game early
+    pub host: Option<UserId>,
+    /// Users following the game without playing in it
+    pub spectators: HashSet<UserId>,
 }
 
 impl GameHandler {
     pub fn new() -> Self {
+        let id = Uuid::new_v4();
         Self {
-            id: Uuid::new_v4(),
+            id,
             instance: GameInstance::new(),
             main_orders: MainOrderCollector::new(),
             retreat_orders: RetreatOrderCollector::new(),
             build_orders: BuildOrderCollector::new(),
+            last_results: Vec::new(),
+            deadlines: PhaseDeadlines::default(),
+            deadline: None,
+            civil_disorder_after: DEFAULT_CIVIL_DISORDER_AFTER,
+            draw_votes: HashSet::new(),
+            result: None,
+            name: id.to_string(),
+            press: PressMode::default(),
+            password_hash: None,
+            assignment: NationAssignment::default(),
+            seed: rand::random(),
+            preferences: HashMap::new(),
+            host: None,
+            spectators: HashSet::new(),
         }
     }
 
-    pub fn try_join(&mut self, user_id: UserId) -> Result<(), JoinError> {
+    /// Seats the user, `password` has to match for games that were created with one. Until the
+    /// game starts players wait without a nation, it starts by itself once every seat is taken
+    pub fn try_join(&mut self, user_id: UserId, password: Option<&str>) -> Result<(), JoinError> {
+        if self.result.is_some() {
+            return Err(JoinError::GameFinished);
+        }
+        if self.instance.has_joined(&user_id) {
+            return Err(JoinError::AlreadyJoined);
+        }
+        self.check_password(password)?;
+        if self.instance.has_started() {
+            let nation = self.instance.abandoned_nation().ok_or(JoinError::GameStarted)?;
+            self.spectators.remove(&user_id);
+            self.take_over(user_id, nation);
+            return Ok(());
+        }
         if self.instance.is_full() {
-            return Err(JoinError);
+            return Err(JoinError::GameFull);
         }
-        if self.instance.players.contains_key(&user_id) {
-            return Err(JoinError);
+
+        self.spectators.remove(&user_id);
+        self.host.get_or_insert(user_id);
+        self.instance.waiting.push(user_id);
+        if self.instance.is_full() {
+            self.start();
         }
+        Ok(())
+    }
 
-        let taken: HashSet<&Nation> = self.instance.players.values().collect();
+    /// Lets a user follow the game without playing, finished games included. Private games
+    /// need their password for this too
+    pub fn spectate(&mut self, user_id: UserId, password: Option<&str>) -> Result<(), JoinError> {
+        if self.instance.has_joined(&user_id) {
+            return Err(JoinError::AlreadyJoined);
+        }
+        self.check_password(password)?;
+        self.spectators.insert(user_id);
+        Ok(())
+    }
+
+    /// Returns whether the user was spectating
+    pub fn stop_spectating(&mut self, user_id: &UserId) -> bool {
+        self.spectators.remove(user_id)
+    }
+
+    fn check_password(&self, password: Option<&str>) -> Result<(), JoinError> {
+        match &self.password_hash {
+            Some(hash) if !password.is_some_and(|password| verify_password(password, hash)) => Err(JoinError::WrongPassword),
+            _ => Ok(()),
+        }
+    }
+
+    /// Lets the creator start before every seat is taken, once enough players have joined
+    pub fn start_early(&mut self, user_id: UserId) -> Result<(), OrderError> {
+        if !self.instance.has_joined(&user_id) {
+            return Err(OrderError::NotInGame);
+        }
+        if self.result.is_some() {
+            return Err(OrderError::GameFinished);
+        }
+        if self.instance.has_started() {
+            return Err(OrderError::GameStarted);
+        }
+        if self.host != Some(user_id) {
+            return Err(OrderError::NotHost);
+        }
+        if self.instance.joined_count() < self.instance.min_players {
+            return Err(OrderError::NotEnoughPlayers { needed: self.instance.min_players });
+        }
+        self.start();
+        Ok(())
+    }
+
+    /// Takes a waiting player back out, which is only possible before nations are handed out.
+    /// If the creator leaves, whoever joined next may start the game instead
+    pub fn leave(&mut self, user_id: UserId) -> Result<(), OrderError> {
+        if !self.instance.has_joined(&user_id) {
+            return Err(OrderError::NotInGame);
+        }
+        if self.instance.has_started() {
+            return Err(OrderError::GameStarted);
+        }
+        self.instance.waiting.retain(|user| user != &user_id);
+        self.preferences.remove(&user_id);
+        if self.host == Some(user_id) {
+            self.host = self.instance.waiting.first().copied();
+        }
+        Ok(())
+    }
+
+    /// Ranks the nations a waiting player would like, only used when nations go by preference
+    pub fn set_preferences(&mut self, user_id: UserId, nations: Vec<Nation>) -> Result<(), OrderError> {
+        if !self.instance.has_joined(&user_id) {
+            return Err(OrderError::NotInGame);
+        }
+        if self.instance.has_started() {
+            return Err(OrderError::GameStarted);
+        }
+        let free = self.instance.free_nations();
+        let distinct: HashSet<&Nation> = nations.iter().collect();
+        if distinct.len() != nations.len() || !nations.iter().all(|nation| free.contains(nation)) {
+            return Err(OrderError::UnknownNation);
+        }
+        self.preferences.insert(user_id, nations);
+        Ok(())
+    }
 
-        // TODO: Make this random, but for testing it's deterministic
-        let nation = ["ENG", "FRA", "GER", "ITA", "AUS", "RUS", "TUR"]
-            .into_iter()
-            .map(Nation::from)
-            .find(|n| !taken.contains(n))
-            .expect("No available nations, but game is not full");
+    /// Hands out nations to everyone waiting and starts the clock on the first phase
+    fn start(&mut self) {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let waiting = std::mem::take(&mut self.instance.waiting);
+        let free = self.instance.free_nations();
+        let assigned = nation_assignment::assign(self.assignment, &waiting, &free, &self.preferences, &mut rng);
+        self.instance.players.extend(assigned);
+        self.preferences.clear();
+        self.instance.started = true;
+        self.start_clock();
+    }
 
+    /// Hands a nation in civil disorder to a new player, who picks up from the current phase
+    fn take_over(&mut self, user_id: UserId, nation: Nation) {
+        self.instance.players.retain(|_, n| n != &nation);
         self.instance.players.insert(user_id, nation);
+        self.attended(&user_id);
+        self.pre_add_readiness();
+    }
+
+    /// The player has done something this phase, so they are not missing
+    fn attended(&mut self, user_id: &UserId) {
+        if let Some(nation) = self.instance.players.get(user_id) {
+            self.instance.missed_phases.remove(nation);
+            self.instance.civil_disorder.remove(nation);
+        }
+    }
+
+    /// Marks a player ready or not, resolving the phase once everyone is ready
+    pub fn set_ready(&mut self, user_id: UserId, ready: bool) -> Result<OrderOutcome, OrderError> {
+        self.check_playing(&user_id)?;
+        self.attended(&user_id);
+
+        let phase = self.instance.phase();
+        let all_ready = match phase {
+            Phase::Main => Self::ready_with(&self.instance, &mut self.main_orders, user_id, ready),
+            Phase::Retreat => Self::ready_with(&self.instance, &mut self.retreat_orders, user_id, ready),
+            Phase::Build => Self::ready_with(&self.instance, &mut self.build_orders, user_id, ready),
+        };
+        if !all_ready {
+            return Ok(OrderOutcome::Accepted);
+        }
+
+        self.resolve_phase()?;
+        // Resolving can hand someone the win
+        if let Some(result) = &self.result {
+            return Ok(OrderOutcome::GameFinished(result.clone()));
+        }
+        Ok(OrderOutcome::GameAdvanced)
+    }
+
+    /// Votes for or against a draw, the game is drawn between every surviving nation once
+    /// all the players still taking part have voted for one
+    pub fn set_draw_vote(&mut self, user_id: UserId, draw: bool) -> Result<OrderOutcome, OrderError> {
+        self.check_playing(&user_id)?;
+        self.attended(&user_id);
+
+        if !draw {
+            self.draw_votes.remove(&user_id);
+            return Ok(OrderOutcome::Accepted);
+        }
+        self.draw_votes.insert(user_id);
+        let agreed = self.instance.has_started()
+            && self.instance.attending_players().all(|user| self.draw_votes.contains(user));
+        if !agreed {
+            return Ok(OrderOutcome::Accepted);
+        }
+
+        let mut nations: Vec<Nation> = self
+            .instance
+            .active_players()
+            .filter_map(|user| self.instance.players.get(user).cloned())
+            .collect();
+        nations.sort_by_key(|nation| nation.to_string());
+        let result = GameResult::Draw { nations };
+        self.finish(result.clone());
+        Ok(OrderOutcome::GameFinished(result))
+    }
+
+    /// Orders and votes only count from players in a game that is still going
+    fn check_playing(&self, user_id: &UserId) -> Result<(), OrderError> {
+        if self.instance.waiting.contains(user_id) {
+            return Err(OrderError::GameNotStarted);
+        }
+        if !self.instance.players.contains_key(user_id) {
+            return Err(OrderError::NotInGame);
+        }
+        if self.result.is_some() {
+            return Err(OrderError::GameFinished);
+        }
         Ok(())
     }
 
+    /// Ends the game, the clock stops so the deadline timer leaves it alone
+    fn finish(&mut self, result: GameResult) {
+        self.result = Some(result);
+        self.deadline = None;
+    }
+
+    /// Adjudicates the current phase with whatever has been given so far. Unordered units
+    /// hold, unordered retreats disband and unused builds are waived
+    pub fn resolve_phase(&mut self) -> Result<(), OrderError> {
+        match self.instance.phase() {
+            Phase::Main => self.resolve_main(),
+            Phase::Retreat => self.resolve_retreat(),
+            Phase::Build => self.resolve_build(),
+        }
+    }
+
+    /// Adjudicates a phase whose deadline has passed. Players who gave no orders and never
+    /// readied up have missed it, returns the nations that fell into civil disorder because of it.
+    /// If the phase cannot be adjudicated the clock is restarted, so it is tried again after
+    /// another full deadline rather than on every tick
+    pub fn resolve_overdue(&mut self) -> Result<Vec<Nation>, OrderError> {
+        let absent: Vec<Nation> = self
+            .instance
+            .attending_players()
+            .filter(|user| !self.has_acted(user))
+            .filter_map(|user| self.instance.players.get(user).cloned())
+            .collect();
+        if let Err(e) = self.resolve_phase() {
+            self.start_clock();
+            return Err(e);
+        }
+
+        let mut abandoned = Vec::new();
+        for nation in absent {
+            let missed = self.instance.missed_phases.entry(nation.clone()).or_default();
+            *missed += 1;
+            if *missed >= self.civil_disorder_after {
+                self.instance.civil_disorder.insert(nation.clone());
+                abandoned.push(nation);
+            }
+        }
+        Ok(abandoned)
+    }
+
+    fn has_acted(&self, user_id: &UserId) -> bool {
+        match self.instance.phase() {
+            Phase::Main => self.main_orders.has_orders(user_id) || self.main_orders.is_player_ready(user_id),
+            Phase::Retreat => self.retreat_orders.has_orders(user_id) || self.retreat_orders.is_player_ready(user_id),
+            Phase::Build => self.build_orders.has_orders(user_id) || self.build_orders.is_player_ready(user_id),
+        }
+    }
+
+    /// Whether the current phase has run out of time at `now`
+    pub fn is_overdue(&self, now: OffsetDateTime) -> bool {
+        self.deadline.is_some_and(|deadline| deadline <= now)
+    }
+
+    /// Gives the players the full time allowed for the current phase. A length too far off to
+    /// represent leaves the phase without a clock rather than panicking
+    fn start_clock(&mut self) {
+        let length = self.deadlines.for_phase(self.instance.phase());
+        self.deadline = time::Duration::try_from(length)
+            .ok()
+            .and_then(|length| OffsetDateTime::now_utc().checked_add(length));
+        if self.deadline.is_none() {
+            eprintln!("[GAME_ERROR] Deadline of {length:?} for game {} is too long, the phase has no clock", self.id);
+        }
+    }
+
+    /// The player's units still waiting on an order this phase, sorted by region
+    pub fn units_without_orders(&self, user_id: &UserId) -> Result<Vec<(UnitType, RegionKey)>, OrderError> {
+        let nation = self.instance.players.get(user_id).ok_or(OrderError::NotInGame)?;
+        let mut units = match self.instance.phase() {
+            Phase::Main => self.main_orders.units_without_orders(&self.instance, user_id),
+            // Retreats are given all at once
+            Phase::Retreat if !self.retreat_orders.has_orders(user_id) => self
+                .instance
+                .pending_retreats
+                .iter()
+                .filter(|r| &r.nation == nation)
+                .map(|r| (r.unit_type, r.from.clone()))
+                .collect(),
+            Phase::Retreat | Phase::Build => Vec::new(),
+        };
+        units.sort_by_key(|(_, region)| region.to_string());
+        Ok(units)
+    }
+
 // Main
 
     pub fn resolve_main(&mut self) -> Result<(), OrderError> {
-        let orders = self.main_orders.all_orders();
-        let submission = Submission::with_inferred_state(self.instance.map_used(), orders);
+        let orders = self.main_orders.orders_with_holds(&self.instance);
+        let submission = Submission::with_inferred_state(self.instance.map_used(), orders.clone());
         let outcome = submission.adjudicate(Rulebook::default());
+        self.last_results = outcome
+            .all_orders_with_outcomes()
+            .map(|(order, result)| order_result(order, result.into(), result))
+            .collect();
 
         let retreat = outcome.to_retreat_start();
 
@@ -125,7 +525,15 @@ impl GameHandler {
         self.instance.pending_retreats.clear();
 
         for (nation, unit_type, from, available) in retreat_data {
-            if !available.is_empty() {
+            if available.is_empty() {
+                // Nowhere to go, so the unit is disbanded without asking
+                let order = MappedRetreatOrder::new(nation, unit_type, from, RetreatCommand::Hold);
+                self.last_results.push(OrderResult {
+                    order: order.to_string(),
+                    succeeded: true,
+                    outcome: "NoRetreatAvailable".to_string(),
+                });
+            } else {
                 self.instance.pending_retreats.push(PendingRetreat {
                     nation,
                     unit_type,
@@ -135,38 +543,15 @@ impl GameHandler {
             }
         }
 
-        let calendar = Calendar::new(
-            self.instance.time.clone(),
-            vec![(Spring, Main), (Fall, Main), (Winter, Build)],
-        ).unwrap();
-
-        // TODO: This needs to be done for all of them not just this
-        self.instance.time = if self.instance.pending_retreats.is_empty() {
-            println!("[DEBUG] Moving onto build phase as there are no retreats!");
-            calendar.iter().nth(2).expect("No next time available")
+        // The retreat judge is set up from the movement phase, so keep its orders until then
+        self.instance.dislodging_orders = if self.instance.pending_retreats.is_empty() {
+            Vec::new()
         } else {
-            calendar.iter().nth(1).expect("No next time available")
+            orders
         };
 
         self.main_orders.clear();
-        println!("[DEBUG] This is the phase we are currently in! {:?}", self.instance.time);
-        
-        // match self.instance.phase {
-        //     Phase::Build => {
-        //         // Now we will check to see if it will be prereadied
-        //         self.build_orders.pre_add_readiness(&self.instance);
-        //         if self.build_orders.all_players_ready() {
-        //             self.resolve_build()?;
-        //         }
-        //     }
-        //     Phase::Retreat => {
-        //         self.retreat_orders.pre_add_readiness(&self.instance);
-        //         if self.build_orders.all_players_ready() {
-        //             self.resolve_build()?;
-        //         }
-        //     }
-        //     _ => {}
-        // }
+        self.advance();
         Ok(())
     }
 
@@ -175,58 +560,46 @@ impl GameHandler {
         user_id: UserId,
         orders: Vec<MappedMainOrder>,
     ) -> Result<OrderOutcome, OrderError> {
-        let ready = Self::receive_with(
-            &self.instance,
-            &mut self.main_orders,
-            user_id,
-            orders,
-        )?;
-
-        if ready {
-            println!("[DEBUG] Moving on to resolving!");
-            self.resolve_main()?;
-            Ok(OrderOutcome::GameAdvanced)
-        } else {
-            Ok(OrderOutcome::Accepted)
+        if self.result.is_some() {
+            return Err(OrderError::GameFinished);
         }
+        self.main_orders.submit_order(&self.instance, user_id, orders)?;
+        self.attended(&user_id);
+        Ok(OrderOutcome::Accepted)
     }
 
 // Retreat
 
     pub fn resolve_retreat(&mut self) -> Result<(), OrderError> {
-        if self.instance.phase != Phase::Retreat {
+        if self.instance.phase() != Phase::Retreat {
             return Err(OrderError::WrongPhase);
         }
 
-        let orders = self.retreat_orders.all_orders();
-
-        let mut placements = Vec::new();
-        let mut claims: HashMap<RegionKey, Vec<&PendingRetreat>> = HashMap::new();
-
-        for r in &self.instance.pending_retreats {
-            if let Some(ord) = orders.iter().find(|o| o.region == r.from) {
-                if let Some(dest) = ord.command.move_dest() {
-                    if r.options.contains(&dest) {
-                        claims.entry(dest.clone()).or_default().push(r);
-                    }
-                }
-            }
-        }
-
-        for (dest, units) in claims {
-            if units.len() == 1 {
-                let u = units[0];
-                placements.push(UnitPosition::new(
-                    Unit::new(Cow::Owned(u.nation.clone()), u.unit_type),
-                    dest,
-                ));
-            }
-        }
+        let (results, positions) = {
+            let main = Submission::with_inferred_state(
+                self.instance.map_used(),
+                self.instance.dislodging_orders.clone(),
+            );
+            let main_outcome = main.adjudicate(Rulebook::default());
+            let start = main_outcome.to_retreat_start();
+            let context = retreat::Context::new(&start, self.retreat_orders.all_orders());
+            let outcome = context.resolve();
+
+            // Units that had nowhere to go were already reported as disbanded after the movement phase
+            let results: Vec<_> = outcome
+                .order_outcomes()
+                .filter(|(order, _)| self.instance.pending_retreats.iter().any(|r| r.from == order.region))
+                .map(|(order, result)| order_result(order, result.into(), result))
+                .collect();
+            (results, owned_positions(outcome.unit_positions()))
+        };
 
-        self.instance.apply_new_positions(placements);
+        self.last_results = results;
+        self.instance.apply_new_positions(positions);
         self.instance.pending_retreats.clear();
-        self.instance.phase = Phase::Build;
+        self.instance.dislodging_orders.clear();
         self.retreat_orders.clear();
+        self.advance();
         Ok(())
     }
 
@@ -235,24 +608,21 @@ impl GameHandler {
         user_id: UserId,
         orders: Vec<MappedRetreatOrder>,
     ) -> Result<OrderOutcome, OrderError> {
-        let ready = Self::receive_with(
-            &self.instance,
-            &mut self.retreat_orders,
-            user_id,
-            orders,
-        )?;
-
-        if ready {
-            self.resolve_retreat()?;
-            Ok(OrderOutcome::GameAdvanced)
-        } else {
-            Ok(OrderOutcome::Accepted)
+        if self.result.is_some() {
+            return Err(OrderError::GameFinished);
         }
+        self.retreat_orders.submit_order(&self.instance, user_id, orders)?;
+        self.attended(&user_id);
+        Ok(OrderOutcome::Accepted)
     }
 
 // Build
 
     pub fn resolve_build(&mut self) -> Result<(), OrderError> {
+        if self.instance.phase() != Phase::Build {
+            return Err(OrderError::WrongPhase);
+        }
+
         let orders = self.build_orders.all_orders();
         let submission = diplomacy::judge::build::Submission::new(
             self.instance.map_used(),
@@ -262,12 +632,33 @@ impl GameHandler {
         );
 
         let outcome = submission.adjudicate(Rulebook::default());
-        let positions: Vec<_> = outcome.to_final_unit_positions().collect();
+        self.last_results = outcome
+            .order_outcomes()
+            .map(|(order, result)| order_result(order, result.into(), result))
+            .collect();
+
+        // The judge leaves alone nations with no centres at all, they lose every unit
+        let centres = self.instance.supply_centre_counts();
+        let positions: Vec<_> = outcome
+            .to_final_unit_positions()
+            .filter(|pos| centres.contains_key(pos.unit.nation()))
+            .collect();
+        let civil_disorder = outcome.to_civil_disorder();
+        drop(outcome);
+
+        // Disbands a nation owed but never gave are chosen for it
+        for pos in civil_disorder {
+            let order = MappedBuildOrder::new_from_position(pos, BuildCommand::Disband);
+            self.last_results.push(OrderResult {
+                order: order.to_string(),
+                succeeded: true,
+                outcome: "CivilDisorder".to_string(),
+            });
+        }
 
         self.instance.apply_new_positions(positions);
-        self.instance.phase = Phase::Main;
-        println!("[DEBUG] Phase changed: Build -> Main");
         self.build_orders.clear();
+        self.advance();
         Ok(())
     }
 
@@ -276,23 +667,24 @@ impl GameHandler {
         user_id: UserId,
         orders: Vec<MappedBuildOrder>,
     ) -> Result<OrderOutcome, OrderError> {
-        let ready = Self::receive_with(
-            &self.instance,
-            &mut self.build_orders,
-            user_id,
-            orders,
-        )?;
-
-        if ready {
-            self.resolve_retreat()?;
-            Ok(OrderOutcome::GameAdvanced)
-        } else {
-            Ok(OrderOutcome::Accepted)
+        if self.result.is_some() {
+            return Err(OrderError::GameFinished);
         }
+        self.build_orders.submit_order(&self.instance, user_id, orders)?;
+        self.attended(&user_id);
+        Ok(OrderOutcome::Accepted)
     }
 }
 
 
+fn order_result(order: &impl fmt::Display, state: OrderState, outcome: impl fmt::Debug) -> OrderResult {
+    OrderResult {
+        order: order.to_string(),
+        succeeded: state == OrderState::Succeeds,
+        outcome: format!("{:?}", outcome),
+    }
+}
+
 fn owned_positions<'a, I>(positions: I) -> Vec<UnitPosition<'static, RegionKey>>
 where
     I: IntoIterator<Item = UnitPosition<'a, &'a RegionKey>>,
@@ -312,20 +704,260 @@ where
 }
 
 impl GameHandler {
-    fn receive_with<O, C>(
-        instance: &GameInstance,
-        collector: &mut C,
-        user_id: UserId,
-        orders: Vec<O>,
-    ) -> Result<bool, OrderError>
+    /// Works out what watchers should be told after `user_id` did something during `submitted_in`,
+    /// `action` describes what their nation did
+    pub fn order_events(
+        &self,
+        user_id: &UserId,
+        submitted_in: Time,
+        action: impl FnOnce(Uuid, Nation) -> GameEvent,
+    ) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        if let Some(nation) = self.instance.players.get(user_id) {
+            events.push(action(self.id, nation.clone()));
+        }
+        // The phase can be resolved whether or not that ended the game
+        if self.has_resolved(&submitted_in) {
+            events.push(self.resolved_event(submitted_in));
+        }
+        events.extend(self.finished_event());
+        events
+    }
+
+    /// How the game looks in the lobby to `viewer`
+    pub fn summary(&self, viewer: &UserId) -> GameSummary {
+        let players = self.instance.joined_count();
+        let seats = self.instance.seats;
+        let open_seats = match self.result {
+            Some(_) => 0,
+            None if self.instance.has_started() => usize::from(self.instance.abandoned_nation().is_some()),
+            None => seats.saturating_sub(players),
+        };
+        GameSummary {
+            id: self.id,
+            name: self.name.clone(),
+            players,
+            min_players: self.instance.min_players,
+            seats,
+            open_seats,
+            time: self.instance.time().clone(),
+            variant: self.instance.map_kind(),
+            press: self.press,
+            deadlines: self.deadlines,
+            assignment: self.assignment,
+            private: self.password_hash.is_some(),
+            started: self.instance.has_started(),
+            joined: self.instance.has_joined(viewer),
+            spectators: self.spectators.len(),
+            result: self.result.clone(),
+        }
+    }
+
+    /// Tells watchers the game is over, `None` while it is still being played
+    pub fn finished_event(&self) -> Option<GameEvent> {
+        self.result.clone().map(|result| GameEvent::GameFinished { game: self.id, result })
+    }
+
+    /// Whether orders given during `phase` could still change, so have to stay secret. They come
+    /// out once the phase is resolved or the game is over
+    pub fn is_pending(&self, phase: &Time) -> bool {
+        self.result.is_none() && phase >= self.instance.time()
+    }
+
+    /// Whether the game has moved on from `phase`
+    pub fn has_resolved(&self, phase: &Time) -> bool {
+        self.instance.time() != phase
+    }
+
+    /// Tells watchers how `resolved` turned out and which phase the game is in now
+    pub fn resolved_event(&self, resolved: Time) -> GameEvent {
+        let mut results = self.last_results.clone();
+        results.sort_by(|a, b| a.order.cmp(&b.order));
+        GameEvent::PhaseResolved {
+            game: self.id,
+            resolved,
+            next: self.instance.time().clone(),
+            results,
+        }
+    }
+
+    /// Moves the game on and readies everyone with nothing to do in the phase it lands on,
+    /// unless a nation now holds enough supply centres to win
+    fn advance(&mut self) {
+        self.instance.advance();
+        if let Some(winner) = self.instance.winner() {
+            self.finish(GameResult::Victory { winner });
+            return;
+        }
+        self.pre_add_readiness();
+        self.start_clock();
+    }
+
+    fn pre_add_readiness(&mut self) {
+        match self.instance.phase() {
+            Phase::Retreat => self.retreat_orders.pre_add_readiness(&self.instance),
+            Phase::Build => self.build_orders.pre_add_readiness(&self.instance),
+            Phase::Main => self.main_orders.pre_add_readiness(&self.instance),
+        }
+    }
+
+    fn ready_with<O, C>(instance: &GameInstance, collector: &mut C, user_id: UserId, ready: bool) -> bool
     where
         C: OrderCollector<O>,
     {
-        if collector.is_player_ready(&user_id) {
-            return Err(OrderError::UserReadied);
+        if ready {
+            collector.mark_ready(user_id);
+        } else {
+            collector.unmark_ready(user_id);
+        }
+        ready && collector.all_players_ready(instance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use diplomacy::Season;
+
+    use super::*;
+
+    /// A game with `players` seats, all taken so it has started
+    fn started_game(players: usize) -> (GameHandler, Vec<UserId>) {
+        let mut gh = GameHandler::new();
+        gh.instance.seats = players;
+        gh.instance.min_players = players;
+        let users: Vec<UserId> = (0..players).map(|_| Uuid::new_v4()).collect();
+        for user in &users {
+            gh.try_join(*user, None).unwrap();
         }
+        (gh, users)
+    }
+
+    #[test]
+    fn the_phase_waits_for_every_player() {
+        let (mut gh, users) = started_game(2);
+        assert!(matches!(gh.set_ready(users[0], true), Ok(OrderOutcome::Accepted)));
+        assert_eq!(gh.instance.time(), &Time::new(Season::Spring, 1901, Phase::Main));
+    }
+
+    #[test]
+    fn the_phase_resolves_once_the_players_in_the_game_are_ready() {
+        let (mut gh, users) = started_game(2);
+        gh.set_ready(users[0], true).unwrap();
+        assert!(matches!(gh.set_ready(users[1], true), Ok(OrderOutcome::GameAdvanced)));
+        assert_eq!(gh.instance.time(), &Time::new(Season::Fall, 1901, Phase::Main));
+    }
+
+    #[test]
+    fn withdrawing_readiness_keeps_the_phase_open() {
+        let (mut gh, users) = started_game(2);
+        gh.set_ready(users[0], true).unwrap();
+        gh.set_ready(users[0], false).unwrap();
+        assert!(matches!(gh.set_ready(users[1], true), Ok(OrderOutcome::Accepted)));
+        assert_eq!(gh.instance.time(), &Time::new(Season::Spring, 1901, Phase::Main));
+    }
+
+    #[test]
+    fn the_clock_runs_from_the_start_of_the_game() {
+        let (gh, _) = started_game(2);
+        let deadline = gh.deadline.expect("a started game has a deadline");
+        assert!(!gh.is_overdue(OffsetDateTime::now_utc()));
+        assert!(gh.is_overdue(deadline));
+    }
+
+    #[test]
+    fn overdue_phases_are_resolved_and_absent_players_miss_them() {
+        let (mut gh, users) = started_game(2);
+        gh.set_ready(users[0], true).unwrap();
+        let present = gh.instance.players[&users[0]].clone();
+        let absent = gh.instance.players[&users[1]].clone();
+
+        let abandoned = gh.resolve_overdue().unwrap();
+        assert!(abandoned.is_empty());
+        assert_eq!(gh.instance.time(), &Time::new(Season::Fall, 1901, Phase::Main));
+        assert_eq!(gh.instance.missed_phases.get(&absent), Some(&1));
+        assert_eq!(gh.instance.missed_phases.get(&present), None);
+    }
+
+    #[test]
+    fn missing_deadlines_in_a_row_puts_a_nation_into_civil_disorder() {
+        let (mut gh, users) = started_game(2);
+        let absent = gh.instance.players[&users[1]].clone();
+        gh.set_ready(users[0], true).unwrap();
+        assert!(gh.resolve_overdue().unwrap().is_empty());
+        gh.set_ready(users[0], true).unwrap();
+        assert_eq!(gh.resolve_overdue().unwrap(), vec![absent.clone()]);
+        assert!(gh.instance.civil_disorder.contains(&absent));
+
+        // Nobody waits on a nation in civil disorder
+        assert!(matches!(gh.set_ready(users[0], true), Ok(OrderOutcome::GameAdvanced)));
+    }
+
+    #[test]
+    fn a_new_player_can_take_over_a_nation_in_civil_disorder() {
+        let (mut gh, users) = started_game(2);
+        let abandoned = gh.instance.players[&users[1]].clone();
+        gh.instance.civil_disorder.insert(abandoned.clone());
+
+        let newcomer = Uuid::new_v4();
+        gh.try_join(newcomer, None).unwrap();
+        assert_eq!(gh.instance.players.get(&newcomer), Some(&abandoned));
+        assert!(!gh.instance.players.contains_key(&users[1]));
+        assert!(!gh.instance.civil_disorder.contains(&abandoned));
+        assert!(matches!(gh.try_join(Uuid::new_v4(), None), Err(JoinError::GameStarted)));
+    }
+
+    #[test]
+    fn readying_up_into_a_solo_win_finishes_the_game() {
+        let (mut gh, users) = started_game(2);
+        let winner = gh.instance.players[&users[0]].clone();
+        let centres: Vec<_> = gh.instance.last_owners.keys().cloned().collect();
+        for centre in centres.into_iter().take(gh.instance.map_kind().victory_centres()) {
+            gh.instance.last_owners.insert(centre, winner.clone());
+        }
+
+        gh.set_ready(users[0], true).unwrap();
+        let outcome = gh.set_ready(users[1], true).unwrap();
+        assert!(matches!(&outcome, OrderOutcome::GameFinished(GameResult::Victory { winner: w }) if *w == winner));
+        let events = gh.order_events(&users[1], Time::new(Season::Spring, 1901, Phase::Main), |game, nation| {
+            GameEvent::ReadinessChanged { game, nation, ready: true }
+        });
+        assert!(events.iter().any(|e| matches!(e, GameEvent::PhaseResolved { .. })));
+        assert!(events.iter().any(|e| matches!(e, GameEvent::GameFinished { .. })));
+    }
+
+    #[test]
+    fn leaving_before_the_start_frees_the_seat_and_passes_on_hosting() {
+        let mut gh = GameHandler::new();
+        let (host, second) = (Uuid::new_v4(), Uuid::new_v4());
+        gh.try_join(host, None).unwrap();
+        gh.try_join(second, None).unwrap();
+
+        gh.leave(host).unwrap();
+        assert!(!gh.instance.has_joined(&host));
+        assert_eq!(gh.instance.joined_count(), 1);
+        assert_eq!(gh.host, Some(second));
+        assert!(matches!(gh.leave(host), Err(OrderError::NotInGame)));
+    }
+
+    #[test]
+    fn nobody_can_leave_once_the_game_has_started() {
+        let (mut gh, users) = started_game(2);
+        assert!(matches!(gh.leave(users[0]), Err(OrderError::GameStarted)));
+        assert!(gh.instance.players.contains_key(&users[0]));
+    }
 
-        collector.submit_order(instance, user_id, orders)?;
-        Ok(collector.all_players_ready())
+    #[test]
+    fn the_lobby_counts_each_spectator_until_they_stop() {
+        let (mut gh, users) = started_game(2);
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        gh.spectate(first, None).unwrap();
+        gh.spectate(second, None).unwrap();
+        gh.spectate(second, None).unwrap();
+        assert_eq!(gh.summary(&first).spectators, 2);
+
+        assert!(gh.stop_spectating(&first));
+        assert!(!gh.stop_spectating(&first));
+        assert_eq!(gh.summary(&first).spectators, 1);
+        assert!(matches!(gh.spectate(users[0], None), Err(JoinError::AlreadyJoined)));
     }
 }
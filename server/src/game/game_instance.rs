@@ -3,12 +3,17 @@ use std::str::FromStr;
 use std::borrow::Cow;
 
 use diplomacy::{Calendar, Time, Season};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use diplomacy::{
-    Nation, Phase, Unit, UnitPosition, UnitType,
-    geo::{Map, ProvinceKey, RegionKey, standard_map},
+    Nation, Phase, ShortName, UnitPosition, UnitType,
+    geo::{Map, ProvinceKey, RegionKey, SupplyCenter, Terrain, standard_map},
 };
-use common::context::{GameContext, MapKind};
+use common::context::{GameContext, MapKind, SupplyCentreCount};
+use diplomacy::judge::MappedMainOrder;
+use diplomacy::judge::build::to_initial_ownerships;
+
+use super::phase_sequencer;
 
 type UserId = Uuid;
 
@@ -41,7 +46,7 @@ fn get_starting_positions() -> HashMap<Nation, HashSet<(UnitType, RegionKey)>> {
 
 // Stupid crap i need to stop lifetime issues
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PendingRetreat {
     pub nation: Nation,
     pub unit_type: UnitType,
@@ -49,71 +54,258 @@ pub struct PendingRetreat {
     pub options: HashSet<RegionKey>,
 }
 
+#[derive(Clone)]
 pub struct GameInstance {
     pub players: HashMap<UserId, Nation>,
-    pub phase: Phase,
+    /// Players who joined before the game started, in the order they joined. They are
+    /// given a nation and moved into `players` when it starts
+    pub waiting: Vec<UserId>,
+    /// Set once nations are handed out, after that only abandoned nations can be joined
+    pub started: bool,
+    /// How many may join, at most one per nation
+    pub seats: usize,
+    /// How many have to join before the game starts
+    pub min_players: usize,
+    pub variant: MapKind,
 
     map: Map,
+    /// Who owns each supply centre, only changes at the end of Fall
     pub last_owners: HashMap<ProvinceKey, Nation>,
     pub occupiers: HashMap<ProvinceKey, Nation>,
     pub units: HashMap<Nation, HashSet<(UnitType, RegionKey)>>,
 
     pub pending_retreats: Vec<PendingRetreat>,
-    pub time: Time
-}
-
-impl Clone for GameInstance {
-    fn clone(&self) -> Self {
-        Self {
-            players: self.players.clone(),
-            phase: self.phase.clone(),
-            map: self.map.clone(),
-            last_owners: self.last_owners.clone(),
-            occupiers: self.occupiers.clone(),
-            units: self.units.clone(),
-            pending_retreats: self.pending_retreats.clone(),
-            time: Time::new(Season::Spring, 1901, Phase::Main),
-        }
-    }
+    /// The movement orders that dislodged the units in `pending_retreats`, they are
+    /// adjudicated again to set up the retreat judge
+    pub dislodging_orders: Vec<MappedMainOrder>,
+    /// Supply centre counts at the end of each year
+    pub supply_centre_history: Vec<SupplyCentreCount>,
+    /// How many deadlines in a row each nation let pass without giving orders or readying up
+    pub missed_phases: HashMap<Nation, u32>,
+    /// Nations left by their player, their units hold and a new player may take them over
+    pub civil_disorder: HashSet<Nation>,
+    /// Only ever moved on by `advance`, see `phase_sequencer`
+    time: Time
 }
 
 impl std::fmt::Debug for GameInstance {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("GameInstance")
             .field("players", &self.players)
-            .field("phase", &self.phase)
+            .field("waiting", &self.waiting)
+            .field("started", &self.started)
+            .field("seats", &self.seats)
+            .field("min_players", &self.min_players)
+            .field("variant", &self.variant)
             .field("map", &self.map)
             .field("last_owners", &self.last_owners)
             .field("occupiers", &self.occupiers)
             .field("units", &self.units)
             .field("pending_retreats", &self.pending_retreats)
-            .field("time", &"<Calendar>")
+            .field("dislodging_orders", &self.dislodging_orders)
+            .field("supply_centre_history", &self.supply_centre_history)
+            .field("missed_phases", &self.missed_phases)
+            .field("civil_disorder", &self.civil_disorder)
+            .field("time", &self.time)
             .finish()
     }
 }
 
 impl GameInstance {
     pub fn new() -> Self {
+        let map = standard_map().clone();
         Self {
             players: HashMap::with_capacity(7),
-            phase: Phase::Main,
-            map: standard_map().clone(),
-            last_owners: HashMap::new(),
+            waiting: Vec::new(),
+            started: false,
+            seats: MapKind::Standard.nations(),
+            min_players: MapKind::Standard.nations(),
+            variant: MapKind::Standard,
+            last_owners: to_initial_ownerships(&map),
+            map,
             occupiers: HashMap::new(),
             units: get_starting_positions(),
             pending_retreats: Vec::new(),
+            dislodging_orders: Vec::new(),
+            supply_centre_history: Vec::new(),
+            missed_phases: HashMap::new(),
+            civil_disorder: HashSet::new(),
             time: Time::new(Season::Spring, 1901, Phase::Main),
         }
     }
 
     pub fn is_full(&self) -> bool {
-        self.players.len() >= 7
+        self.joined_count() >= self.seats
+    }
+
+    /// Everyone in the game, whether or not they have a nation yet
+    pub fn joined_count(&self) -> usize {
+        self.players.len() + self.waiting.len()
+    }
+
+    pub fn has_joined(&self, user: &UserId) -> bool {
+        self.players.contains_key(user) || self.waiting.contains(user)
+    }
+
+    /// Whether nations have been handed out and the game is being played
+    pub fn has_started(&self) -> bool {
+        self.started
+    }
+
+    /// The nations on the map nobody is playing yet, sorted by name
+    pub fn free_nations(&self) -> Vec<Nation> {
+        let taken: HashSet<&Nation> = self.players.values().collect();
+        let mut free: Vec<Nation> = self
+            .units
+            .keys()
+            .chain(self.last_owners.values())
+            .filter(|nation| !taken.contains(nation))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .cloned()
+            .collect();
+        free.sort_by_key(|nation| nation.to_string());
+        free
+    }
+
+    /// A nation is out of the game once it has neither units nor supply centres
+    pub fn is_eliminated(&self, nation: &Nation) -> bool {
+        self.units.get(nation).is_none_or(HashSet::is_empty)
+            && !self.last_owners.values().any(|owner| owner == nation)
+    }
+
+    /// The players whose nation is still in the game
+    pub fn active_players(&self) -> impl Iterator<Item = &UserId> {
+        self.players
+            .iter()
+            .filter(|(_, nation)| !self.is_eliminated(nation))
+            .map(|(user, _)| user)
+    }
+
+    /// The players still in the game who have not abandoned it, readiness waits on them
+    pub fn attending_players(&self) -> impl Iterator<Item = &UserId> {
+        self.active_players()
+            .filter(|user| !self.players.get(user).is_some_and(|nation| self.civil_disorder.contains(nation)))
+    }
+
+    /// A nation in civil disorder that is still in the game, for a new player to take over
+    pub fn abandoned_nation(&self) -> Option<Nation> {
+        let mut abandoned: Vec<_> = self
+            .civil_disorder
+            .iter()
+            .filter(|nation| !self.is_eliminated(nation))
+            .collect();
+        abandoned.sort_by_key(|nation| nation.to_string());
+        abandoned.first().map(|nation| (*nation).clone())
     }
 
     pub fn map_used(&self) -> &Map {
         &self.map
     }
 
+    pub fn map_kind(&self) -> MapKind {
+        self.variant
+    }
+
+    /// The nation holding enough supply centres to win outright, if any
+    pub fn winner(&self) -> Option<Nation> {
+        let needed = self.map_kind().victory_centres();
+        self.supply_centre_counts()
+            .into_iter()
+            .find(|(_, count)| *count >= needed)
+            .map(|(nation, _)| nation)
+    }
+
+    pub fn time(&self) -> &Time {
+        &self.time
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.time.phase()
+    }
+
+    /// Moves the game on to the next phase anyone has to act in, skipping retreat phases
+    /// with no dislodged units and build phases where nobody gains or loses units.
+    /// Supply centres change hands as the game leaves Fall.
+    pub fn advance(&mut self) -> &Time {
+        loop {
+            let next = phase_sequencer::following(&self.time);
+            if self.time.season() == Season::Fall && next.season() != Season::Fall {
+                self.capture_supply_centres();
+            }
+            self.time = next;
+
+            let has_work = match self.time.phase() {
+                Phase::Main => true,
+                Phase::Retreat => !self.pending_retreats.is_empty(),
+                Phase::Build => self.has_adjustments(),
+            };
+            if has_work {
+                return &self.time;
+            }
+        }
+    }
+
+    /// Puts a restored game back at the phase it was saved in
+    pub fn resume_at(&mut self, time: Time) {
+        self.time = time;
+    }
+
+    /// Hands every occupied supply centre to its occupier and records the year's counts
+    fn capture_supply_centres(&mut self) {
+        for prov in self.map.provinces().filter(|p| p.is_supply_center()) {
+            let key: ProvinceKey = prov.into();
+            if let Some(n) = self.occupiers.get(&key) {
+                self.last_owners.insert(key, n.clone());
+            }
+        }
+        self.supply_centre_history.push(SupplyCentreCount {
+            year: self.time.year(),
+            centres: self.supply_centre_counts(),
+        });
+    }
+
+    /// How many supply centres each nation that owns any currently has
+    pub fn supply_centre_counts(&self) -> HashMap<Nation, usize> {
+        let mut counts = HashMap::new();
+        for owner in self.last_owners.values() {
+            *counts.entry(owner.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Units a nation may build (positive) or must disband (negative) this winter
+    pub fn adjustment(&self, nation: &Nation) -> i32 {
+        let centres = self.last_owners.values().filter(|owner| *owner == nation).count();
+        let units = self.units.get(nation).map_or(0, HashSet::len);
+        centres as i32 - units as i32
+    }
+
+    /// Whether `nation` may build a `unit_type` at `region`: it has to be one of the nation's
+    /// home centres, still owned by it and empty, and a fleet needs a coast it can sail from
+    pub fn can_build_at(&self, nation: &Nation, unit_type: UnitType, region: &RegionKey) -> bool {
+        let province = region.province();
+        let is_home = self.map.provinces().any(|p| {
+            p == province && matches!(&p.supply_center, SupplyCenter::Home(home) if home == nation)
+        });
+        if !is_home || self.last_owners.get(province) != Some(nation) || self.occupiers.contains_key(province) {
+            return false;
+        }
+
+        match self.map.find_region(&region.short_name()).map(|r| r.terrain()) {
+            Some(Terrain::Coast) => true,
+            Some(Terrain::Land) => unit_type == UnitType::Army,
+            Some(Terrain::Sea) => unit_type == UnitType::Fleet,
+            None => false,
+        }
+    }
+
+    /// Whether any nation controls a different number of supply centres than it has units
+    fn has_adjustments(&self) -> bool {
+        let nations: HashSet<&Nation> = self.units.keys().chain(self.last_owners.values()).collect();
+        nations.into_iter().any(|nation| self.adjustment(nation) != 0)
+    }
+
     pub fn apply_new_positions<I>(&mut self, positions: I)
     where
         I: IntoIterator<Item = UnitPosition<'static, RegionKey>>,
@@ -131,25 +323,29 @@ impl GameInstance {
             self.units.entry(nation.clone()).or_default().insert((ut, region));
             self.occupiers.insert(province, nation);
         }
-
-        for prov in self.map.provinces().filter(|p| p.is_supply_center()) {
-            let key: ProvinceKey = prov.into();
-            if let Some(n) = self.occupiers.get(&key) {
-                // println!("[DEBUG] Inserting last owner: key={:?}, nation={:?}", key, n);
-                self.last_owners.insert(key, n.clone());
-            }
-        }
     }
 
     pub fn to_context_for(&self, user: &UserId) -> Option<GameContext> {
         let nation = self.players.get(user)?.clone();
-        Some(GameContext::new(
+        Some(self.context(Some(nation)))
+    }
+
+    /// The board as a spectator sees it, everything public without a nation of their own
+    pub fn spectator_context(&self) -> GameContext {
+        self.context(None)
+    }
+
+    fn context(&self, nation: Option<Nation>) -> GameContext {
+        GameContext::new(
             nation,
-            MapKind::Standard,
+            self.map_kind(),
             self.last_owners.clone(),
             self.occupiers.clone(),
             self.units.clone(),
-        ))
+        )
+        .with_supply_centres(self.supply_centre_counts(), self.supply_centre_history.clone())
+        .with_time(self.time.clone())
+        .with_civil_disorder(self.civil_disorder.clone())
     }
 }
 
@@ -158,8 +354,9 @@ impl GameInstance {
 use diplomacy::judge::build::WorldState;
 
 impl WorldState for GameInstance {
+    /// Nations with units or supply centres, a nation that lost every unit can still build
     fn nations(&self) -> HashSet<&Nation> {
-        self.units.keys().collect()
+        self.units.keys().chain(self.last_owners.values()).collect()
     }
 
     fn occupier(&self, province: &ProvinceKey) -> Option<&Nation> {
@@ -214,3 +411,47 @@ impl GameInstance {
             .unwrap_or_default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn retreat_from(nation: &str, from: &str) -> PendingRetreat {
+        PendingRetreat {
+            nation: Nation::from(nation),
+            unit_type: UnitType::Army,
+            from: RegionKey::from_str(from).unwrap(),
+            options: HashSet::new(),
+        }
+    }
+
+    #[test]
+    fn retreats_are_skipped_when_nothing_was_dislodged() {
+        let mut game = GameInstance::new();
+        assert_eq!(game.advance(), &Time::new(Season::Fall, 1901, Phase::Main));
+    }
+
+    #[test]
+    fn retreats_are_played_when_a_unit_was_dislodged() {
+        let mut game = GameInstance::new();
+        game.pending_retreats.push(retreat_from("AUS", "vie"));
+        assert_eq!(game.advance(), &Time::new(Season::Spring, 1901, Phase::Retreat));
+    }
+
+    #[test]
+    fn winter_is_skipped_when_no_nation_has_adjustments() {
+        let mut game = GameInstance::new();
+        game.resume_at(Time::new(Season::Fall, 1901, Phase::Main));
+        assert_eq!(game.advance(), &Time::new(Season::Spring, 1902, Phase::Main));
+    }
+
+    #[test]
+    fn winter_is_played_when_a_nation_is_owed_a_build() {
+        let mut game = GameInstance::new();
+        game.resume_at(Time::new(Season::Fall, 1901, Phase::Main));
+        let ber = RegionKey::from_str("ber").unwrap();
+        game.units.get_mut(&Nation::from("GER")).unwrap().remove(&(UnitType::Army, ber));
+        assert_eq!(game.advance(), &Time::new(Season::Winter, 1901, Phase::Build));
+        assert_eq!(game.adjustment(&Nation::from("GER")), 1);
+    }
+}
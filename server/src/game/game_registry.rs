@@ -1,13 +1,20 @@
 use std::collections::HashMap;
+use common::protocol::GameEvent;
 use uuid::Uuid;
 use once_cell::sync::Lazy;
+use time::OffsetDateTime;
+use tokio::sync::broadcast;
 use tokio::sync::RwLock;
 
 
 use super::game_handler::GameHandler;
 
+/// How many events a slow watcher can fall behind before it starts missing them
+const EVENT_CAPACITY: usize = 64;
+
 pub struct GameRegistry {
     games: HashMap<Uuid, GameHandler>,
+    events: HashMap<Uuid, broadcast::Sender<GameEvent>>,
 }
 impl GameRegistry{
     /// The Game Registry maps all GameHandlers to games at the runtime 
@@ -15,17 +22,20 @@ impl GameRegistry{
     pub fn new() -> Self {
         // Initalising the games 
         //  (I need to change this in a sec to the fact that it should check for games already in the db)
-        Self { games: HashMap::new() }
+        Self { games: HashMap::new(), events: HashMap::new() }
     }
 
     pub fn insert(&mut self, game_handler: GameHandler) {
         // This fucntion creates a new game
+        let (sender, _) = broadcast::channel(EVENT_CAPACITY);
+        self.events.insert(game_handler.id, sender);
         self.games.insert(game_handler.id, game_handler);
 
     }
 
     pub fn delete(&mut self, game_id: &Uuid) {
         self.games.remove(game_id);
+        self.events.remove(game_id);
 
     }
 
@@ -37,7 +47,30 @@ impl GameRegistry{
         self.games.get_mut(game_id)
     }
 
-    
+    pub fn games(&self) -> impl Iterator<Item = &GameHandler> {
+        self.games.values()
+    }
+
+    /// Games whose current phase ran out of time at `now`
+    pub fn overdue_games(&self, now: OffsetDateTime) -> Vec<Uuid> {
+        self.games
+            .values()
+            .filter(|gh| gh.is_overdue(now))
+            .map(|gh| gh.id)
+            .collect()
+    }
+
+    /// Listens to every event published for a game from now on
+    pub fn subscribe(&self, game_id: &Uuid) -> Option<broadcast::Receiver<GameEvent>> {
+        self.events.get(game_id).map(|sender| sender.subscribe())
+    }
+
+    /// Broadcasts an event to everyone watching the game, it is fine if nobody is
+    pub fn publish(&self, game_id: &Uuid, event: GameEvent) {
+        if let Some(sender) = self.events.get(game_id) {
+            let _ = sender.send(event);
+        }
+    }
 }
 
-pub static GAME_REGISTRY: Lazy<RwLock<GameRegistry>> = Lazy::new(|| RwLock::new(GameRegistry::new()));
\ No newline at end of file
+pub static GAME_REGISTRY: Lazy<RwLock<GameRegistry>> = Lazy::new(|| RwLock::new(GameRegistry::new()));
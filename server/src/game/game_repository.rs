@@ -1,15 +1,24 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use sea_orm::{ActiveModelTrait, DbErr};
 use sea_orm::ActiveValue::{Set, NotSet};
 use sea_orm::DatabaseConnection;
 use sea_orm::error;
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 //  The Game Model
 use crate::data::connection_pool::ConnectionPool;
 use crate::data::game::ActiveModel;
-use crate::data::game::GamePhase;
+use crate::data::game::{Column, Entity as Game, GamePhase};
+use crate::data::game_member::{self, Column as MemberColumn, Entity as GameMember};
+use crate::data::game_settings;
+use crate::game::game_handler::GameHandler;
+use crate::game::game_snapshot::GameSnapshot;
+use sea_orm::sea_query::{Expr, OnConflict};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use serde::Serialize;
 
 pub struct GameRepository {
     connection_pool:Arc<ConnectionPool>,
@@ -23,17 +32,204 @@ impl GameRepository {
         }
     }
 
-    pub async fn insert_game(&self, game_id: Uuid) -> Result<(), DbErr>{
+    /// Adds a new game along with the settings it was created with
+    pub async fn insert_game(&self, gh: &GameHandler) -> Result<(), DbErr>{
         let game_year: i32 = 1901;
+        let game_id = gh.id;
         let conn: &DatabaseConnection = self.connection_pool.get_connection();
+        let deadlines = gh.deadlines;
+        let (movement, retreat, build) = (
+            deadline_secs(deadlines.movement)?,
+            deadline_secs(deadlines.retreat)?,
+            deadline_secs(deadlines.build)?,
+        );
         let game_model: ActiveModel = ActiveModel {
             game_id: NotSet,
-            name: Set(game_id.to_string()),
+            game_uuid: Set(Some(game_id)),
+            name: Set(gh.name.clone()),
             year: Set(game_year),
             game_phase: Set(GamePhase::SpringMovement),
-            created_at: NotSet
+            state: NotSet,
+            created_at: NotSet,
+            finished_at: NotSet,
+            result: NotSet,
         };
         game_model.insert(conn).await?;
+
+        let settings = game_settings::ActiveModel {
+            game_uuid: Set(game_id),
+            name: Set(gh.name.clone()),
+            variant: Set(enum_name(&gh.instance.variant)),
+            press: Set(enum_name(&gh.press)),
+            movement_deadline_secs: Set(movement),
+            retreat_deadline_secs: Set(retreat),
+            build_deadline_secs: Set(build),
+            min_players: Set(gh.instance.min_players as i32),
+            max_players: Set(gh.instance.seats as i32),
+            password_hash: Set(gh.password_hash.clone()),
+            created_at: NotSet,
+        };
+        settings.insert(conn).await?;
+        Ok(())
+    }
+
+    /// Removes a game along with its settings and members
+    pub async fn delete_game(&self, game_id: Uuid) -> Result<(), DbErr> {
+        let conn: &DatabaseConnection = self.connection_pool.get_connection();
+        GameMember::delete_many().filter(MemberColumn::GameUuid.eq(game_id)).exec(conn).await?;
+        game_settings::Entity::delete_by_id(game_id).exec(conn).await?;
+        Game::delete_many().filter(Column::GameUuid.eq(game_id)).exec(conn).await?;
+        Ok(())
+    }
+
+    /// Overwrites the saved state of a game along with its year and phase, and
+    /// marks it finished the first time it is saved with a result
+    pub async fn save_game(&self, game_id: Uuid, snapshot: &GameSnapshot) -> Result<(), DbErr> {
+        let conn: &DatabaseConnection = self.connection_pool.get_connection();
+        let state = serde_json::to_value(snapshot).map_err(|e| DbErr::Custom(e.to_string()))?;
+        let Some(game) = Game::find()
+            .filter(Column::GameUuid.eq(game_id))
+            .one(conn)
+            .await?
+        else {
+            return Err(DbErr::RecordNotFound(format!("game {game_id}")));
+        };
+
+        let finished = game.finished_at.is_some();
+        let mut game_model: ActiveModel = game.into();
+        if let (Some(result), false) = (&snapshot.result, finished) {
+            let result = serde_json::to_value(result).map_err(|e| DbErr::Custom(e.to_string()))?;
+            game_model.finished_at = Set(Some(OffsetDateTime::now_utc()));
+            game_model.result = Set(Some(result));
+        }
+        game_model.state = Set(Some(state));
+        game_model.year = Set(snapshot.time.year() as i32);
+        game_model.game_phase = Set(GamePhase::from(&snapshot.time));
+        game_model.update(conn).await?;
+        Ok(())
+    }
+
+    /// Every game that has saved state, ready to be put back in the registry
+    pub async fn load_games(&self) -> Result<Vec<(Uuid, GameSnapshot)>, DbErr> {
+        let conn: &DatabaseConnection = self.connection_pool.get_connection();
+        let games = Game::find()
+            .filter(Column::GameUuid.is_not_null())
+            .filter(Column::State.is_not_null())
+            .all(conn)
+            .await?;
+
+        let mut snapshots = Vec::with_capacity(games.len());
+        for game in games {
+            let (Some(game_id), Some(state)) = (game.game_uuid, game.state) else { continue };
+            match serde_json::from_value::<GameSnapshot>(state) {
+                // Games saved before names were kept in the snapshot
+                Ok(mut snapshot) => {
+                    if snapshot.name.is_empty() {
+                        snapshot.name = game.name;
+                    }
+                    snapshots.push((game_id, snapshot))
+                }
+                Err(e) => eprintln!("[GAME_REPO_ERROR] Could not read the saved state of game {game_id}: {e}"),
+            }
+        }
+        Ok(snapshots)
+    }
+}
+
+impl GameRepository {
+    /// Records that a user is in a game and makes it the one they picked last
+    pub async fn add_member(&self, game_id: Uuid, user_id: Uuid) -> Result<(), DbErr> {
+        let conn: &DatabaseConnection = self.connection_pool.get_connection();
+        let now = OffsetDateTime::now_utc();
+        let member = game_member::ActiveModel {
+            game_uuid: Set(game_id),
+            user_uuid: Set(user_id),
+            joined_at: Set(now),
+            selected_at: Set(now),
+        };
+        GameMember::insert(member)
+            .on_conflict(
+                OnConflict::columns([MemberColumn::GameUuid, MemberColumn::UserUuid])
+                    .update_column(MemberColumn::SelectedAt)
+                    .to_owned(),
+            )
+            .exec_without_returning(conn)
+            .await?;
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Adds whichever of `users` are missing from a game, leaving the rest as they were
+    pub async fn ensure_members(&self, game_id: Uuid, users: impl IntoIterator<Item = Uuid>) -> Result<(), DbErr> {
+        let conn: &DatabaseConnection = self.connection_pool.get_connection();
+        let now = OffsetDateTime::now_utc();
+        let members: Vec<game_member::ActiveModel> = users
+            .into_iter()
+            .map(|user_id| game_member::ActiveModel {
+                game_uuid: Set(game_id),
+                user_uuid: Set(user_id),
+                joined_at: Set(now),
+                selected_at: Set(now),
+            })
+            .collect();
+        if members.is_empty() {
+            return Ok(());
+        }
+        GameMember::insert_many(members)
+            .on_conflict(
+                OnConflict::columns([MemberColumn::GameUuid, MemberColumn::UserUuid])
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(conn)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn remove_member(&self, game_id: Uuid, user_id: Uuid) -> Result<(), DbErr> {
+        let conn: &DatabaseConnection = self.connection_pool.get_connection();
+        GameMember::delete_by_id((game_id, user_id)).exec(conn).await?;
+        Ok(())
+    }
+
+    /// Marks the game as the one the user picked last, returning false if they are not in it
+    pub async fn select_game(&self, game_id: Uuid, user_id: Uuid) -> Result<bool, DbErr> {
+        let conn: &DatabaseConnection = self.connection_pool.get_connection();
+        let updated = GameMember::update_many()
+            .col_expr(MemberColumn::SelectedAt, Expr::value(OffsetDateTime::now_utc()))
+            .filter(MemberColumn::GameUuid.eq(game_id))
+            .filter(MemberColumn::UserUuid.eq(user_id))
+            .exec(conn)
+            .await?;
+        Ok(updated.rows_affected > 0)
+    }
+
+    pub async fn is_member(&self, game_id: Uuid, user_id: Uuid) -> Result<bool, DbErr> {
+        let conn: &DatabaseConnection = self.connection_pool.get_connection();
+        Ok(GameMember::find_by_id((game_id, user_id)).one(conn).await?.is_some())
+    }
+
+    /// The game the user picked most recently, used to put them back in it when they log in
+    pub async fn last_selected_game(&self, user_id: Uuid) -> Result<Option<Uuid>, DbErr> {
+        let conn: &DatabaseConnection = self.connection_pool.get_connection();
+        let member = GameMember::find()
+            .filter(MemberColumn::UserUuid.eq(user_id))
+            .order_by_desc(MemberColumn::SelectedAt)
+            .one(conn)
+            .await?;
+        Ok(member.map(|member| member.game_uuid))
+    }
+}
+
+/// A deadline as stored, refusing lengths too long for the column rather than wrapping them
+fn deadline_secs(length: Duration) -> Result<i64, DbErr> {
+    i64::try_from(length.as_secs())
+        .map_err(|_| DbErr::Custom(format!("A deadline of {} seconds is too long to store", length.as_secs())))
+}
+
+/// How an enum is written in JSON, e.g. `gunboat`
+fn enum_name(value: &impl Serialize) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
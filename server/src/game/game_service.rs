@@ -1,49 +1,63 @@
 use common::context::GameContext;
+use common::protocol::{ErrorCode, ErrorResponse, GameEvent, GameFilter, GameSettings, GameSummary};
+use diplomacy::Nation;
+use sea_orm::DbErr;
 use uuid::Uuid;
 use std::iter::Successors;
 use std::sync::Arc;
+use tokio::sync::broadcast;
 
 use crate::auth::session::Session;
 use crate::game::game_handler::{self, GameHandler, JoinError};
 use crate::game::game_instance::GameInstance;
 use crate::game::game_registry::GameRegistry;
+use common::protocol::PhaseDeadlines;
+use crate::game::game_settings::CreateError;
+use crate::game::game_snapshot::GameSnapshot;
 
 use super::game_repository::GameRepository;
 use super::game_registry::GAME_REGISTRY;
 
 pub struct GameService {
-    game_repo: Arc<GameRepository>
+    game_repo: Arc<GameRepository>,
+    /// Given to every new game
+    deadlines: PhaseDeadlines,
+    /// Missed deadlines in a row before a nation falls into civil disorder, for every new game
+    civil_disorder_after: u32,
 }
 
 impl GameService {
-    pub fn new(given_repo: Arc<GameRepository>) -> Self {
-        Self {game_repo: given_repo}
+    pub fn new(given_repo: Arc<GameRepository>, deadlines: PhaseDeadlines, civil_disorder_after: u32) -> Self {
+        Self {game_repo: given_repo, deadlines, civil_disorder_after}
     }
 
-    pub async fn create_game(&self) -> Uuid {
-        let mut registry = GAME_REGISTRY.write().await;
-        // Create new hadler for the new game
-        let handler: GameHandler = GameHandler::new();
+    /// Sets up a game as `settings` asks, the server's own deadlines are used if it gives none
+    pub async fn create_game(&self, settings: &GameSettings) -> Result<Uuid, CreateError> {
+        let mut handler = GameHandler::configured(settings, self.deadlines).map_err(CreateError::Settings)?;
+        handler.civil_disorder_after = self.civil_disorder_after;
         let game_id: Uuid = handler.id;
+
+        // Db allocation first so a game the database never heard of is never played
+        if let Err(e) = self.game_repo.insert_game(&handler).await {
+            eprintln!("Error failed to add to databse!: {e}");
+            return Err(CreateError::Database(e));
+        }
+        println!("Result is a success! Added to db");
+
         // Runtime allocation
-        registry.insert(handler);
-        
-        // Db allocation
-        let db_result = self.game_repo.insert_game(game_id).await;
-        match db_result {
-            Ok(()) => {
-                println!("Result is a success! Added to db");
-            }
-            Err(e) => {
-                eprintln!("Error failed to add to databse!: {e}");
-                registry.delete(&game_id);
-            }
+        GAME_REGISTRY.write().await.insert(handler);
+        Ok(game_id)
+    }
+
+    /// Removes a game that should never have been made, from the registry and the database
+    pub async fn discard_game(&self, game_id: &Uuid) {
+        GAME_REGISTRY.write().await.delete(game_id);
+        if let Err(e) = self.game_repo.delete_game(*game_id).await {
+            eprintln!("[DB_ERROR] Failed to remove game {game_id}: {e}");
         }
-        // TODO: This doesnt make sense if the game has been deleted from runtime, it will create a key error
-        game_id
     }
 
-    pub async fn join_game(&self, given_id: &Uuid, user_id: Uuid) -> Result<(), JoinError> {
+    pub async fn join_game(&self, given_id: &Uuid, user_id: Uuid, password: Option<&str>) -> Result<(), JoinError> {
         // Join a game using by finding if the game exists, afterwars then update it
         let mut registry = GAME_REGISTRY.write().await;
         // Find game:
@@ -51,12 +65,19 @@ impl GameService {
             Some(gh) => {gh}
             None => {
                 eprintln!("[GAME_SERV_ERROR] Failed to find game! ");
-                // Maybe add more here 
-                return Err(JoinError);
+                return Err(JoinError::GameNotFound);
             }
         };
 
-        match gh.try_join(user_id) {
+        let was_started = gh.instance.has_started();
+        // Whoever holds a nation in civil disorder loses it if this join takes it over
+        let replaced = gh
+            .instance
+            .abandoned_nation()
+            .filter(|_| was_started)
+            .and_then(|nation| gh.instance.players.iter().find(|(_, n)| **n == nation).map(|(user, _)| *user));
+        let joined = gh.try_join(user_id, password);
+        match &joined {
             Err(e) => {
                 eprintln!("[GAME_SERV_ERROR] Failed to join game! {e}");
                 println!("Please try again to join game");
@@ -67,24 +88,244 @@ impl GameService {
         };
 
         println!("[DEBUG] Current users now in game: {:?}", gh.instance.players);
-        
-        Ok(())
+        if joined.is_ok() {
+            // Only players taking over an abandoned nation get one straight away
+            let nation = if was_started { gh.instance.players.get(&user_id).cloned() } else { None };
+            let started = !was_started && gh.instance.has_started();
+            registry.publish(given_id, GameEvent::PlayerJoined { game: *given_id, nation });
+            if started {
+                registry.publish(given_id, GameEvent::GameStarted { game: *given_id });
+            }
+        }
+        drop(registry);
+
+        // The join has already happened, so a failure to record it is only logged
+        if joined.is_ok() {
+            if let Err(e) = self.game_repo.add_member(*given_id, user_id).await {
+                eprintln!("[DB_ERROR] Failed to record {user_id} joining game {given_id}: {e}");
+            }
+            if let Some(replaced) = replaced {
+                if let Err(e) = self.game_repo.remove_member(*given_id, replaced).await {
+                    eprintln!("[DB_ERROR] Failed to remove {replaced} from game {given_id}: {e}");
+                }
+            }
+        }
+
+        joined
 
     }
 
-    pub async fn get_game_state(&self, session: &Session) -> Result<GameContext, String>{
+    pub async fn get_game_state(&self, session: &Session) -> Result<GameContext, ErrorResponse>{
+        let game_id = session
+            .current_game
+            .ok_or(ErrorResponse::new(ErrorCode::NotInGame, "You have not joined a game"))?;
         let registry = GAME_REGISTRY.read().await;
-        let gh: &GameHandler = match registry.get_game(&session.current_game.unwrap()) {
+        let gh: &GameHandler = match registry.get_game(&game_id) {
             Some(gh) => {gh}
             None => {
                 eprintln!("[GAME_SERV_ERROR] Failed to find game! ");
-                // Maybe add more here 
-                return Err("No game found".to_string());
+                return Err(ErrorResponse::new(ErrorCode::GameNotFound, "No game found"));
+            }
+        };
+        if gh.instance.waiting.contains(&session.user) {
+            return Err(ErrorResponse::new(
+                ErrorCode::GameNotStarted,
+                "The game has not started yet, nations are handed out once it does",
+            ));
+        }
+        let context = if gh.spectators.contains(&session.user) {
+            Some(gh.instance.spectator_context())
+        } else {
+            gh.instance.to_context_for(&session.user)
+        };
+        context
+            .map(|context| context.with_deadline(gh.deadline).with_result(gh.result.clone()))
+            .ok_or(ErrorResponse::new(ErrorCode::NotInGame, "You are neither playing nor spectating this game"))
+    }
+
+    /// Lets the user follow a game without playing in it
+    pub async fn spectate_game(&self, game_id: &Uuid, user_id: Uuid, password: Option<&str>) -> Result<(), JoinError> {
+        let mut registry = GAME_REGISTRY.write().await;
+        let gh = registry.get_mut_game(game_id).ok_or(JoinError::GameNotFound)?;
+        gh.spectate(user_id, password)
+    }
+
+    /// Stops a session's user spectating its current game, for sessions that are going away.
+    /// The game is saved if that changed it, a failure is only logged
+    pub async fn forget_spectator(&self, session: &Session) {
+        let Some(game_id) = session.current_game else {
+            return;
+        };
+        if self.stop_spectating(&game_id, &session.user).await {
+            if let Err(e) = self.save_game(&game_id).await {
+                eprintln!("[DB_ERROR] Failed to save game {game_id}: {e}");
             }
+        }
+    }
+
+    /// Returns whether the user was spectating the game
+    pub async fn stop_spectating(&self, game_id: &Uuid, user_id: &Uuid) -> bool {
+        let mut registry = GAME_REGISTRY.write().await;
+        registry
+            .get_mut_game(game_id)
+            .is_some_and(|gh| gh.stop_spectating(user_id))
+    }
+
+    /// Takes a waiting player back out of a game that has not started
+    pub async fn leave_game(&self, game_id: &Uuid, user_id: Uuid) -> Result<(), ErrorResponse> {
+        let mut registry = GAME_REGISTRY.write().await;
+        let gh = registry
+            .get_mut_game(game_id)
+            .ok_or(ErrorResponse::new(ErrorCode::GameNotFound, "No game found"))?;
+        gh.leave(user_id)?;
+        registry.publish(game_id, GameEvent::PlayerLeft { game: *game_id });
+        drop(registry);
+
+        if let Err(e) = self.game_repo.remove_member(*game_id, user_id).await {
+            eprintln!("[DB_ERROR] Failed to remove {user_id} from game {game_id}: {e}");
+        }
+        Ok(())
+    }
+
+    pub async fn is_member(&self, game_id: &Uuid, user_id: Uuid) -> Result<bool, DbErr> {
+        self.game_repo.is_member(*game_id, user_id).await
+    }
+
+    /// Makes the game the user's pick for next time they log in, false if they are not in it
+    pub async fn select_game(&self, game_id: &Uuid, user_id: Uuid) -> Result<bool, DbErr> {
+        self.game_repo.select_game(*game_id, user_id).await
+    }
+
+    /// The game the user was last playing, if they are in any
+    pub async fn last_selected_game(&self, user_id: Uuid) -> Result<Option<Uuid>, DbErr> {
+        self.game_repo.last_selected_game(user_id).await
+    }
+
+    /// Records the nations a waiting player would like to play, best first
+    pub async fn set_preferences(&self, session: &Session, nations: Vec<Nation>) -> Result<(), ErrorResponse> {
+        let game_id = session
+            .current_game
+            .ok_or(ErrorResponse::new(ErrorCode::NotInGame, "You have not joined a game"))?;
+        let mut registry = GAME_REGISTRY.write().await;
+        let gh = registry
+            .get_mut_game(&game_id)
+            .ok_or(ErrorResponse::new(ErrorCode::GameNotFound, "No game found"))?;
+        Ok(gh.set_preferences(session.user, nations)?)
+    }
+
+    /// Starts the session's game before every seat is taken, only its creator may do this
+    pub async fn start_game(&self, session: &Session) -> Result<Uuid, ErrorResponse> {
+        let game_id = session
+            .current_game
+            .ok_or(ErrorResponse::new(ErrorCode::NotInGame, "You have not joined a game"))?;
+        let mut registry = GAME_REGISTRY.write().await;
+        let gh = registry
+            .get_mut_game(&game_id)
+            .ok_or(ErrorResponse::new(ErrorCode::GameNotFound, "No game found"))?;
+        gh.start_early(session.user)?;
+        registry.publish(&game_id, GameEvent::GameStarted { game: game_id });
+        Ok(game_id)
+    }
+
+    /// Writes the current state of a game to the database so it survives a restart
+    pub async fn save_game(&self, game_id: &Uuid) -> Result<(), DbErr> {
+        // Snapshot under the lock, but do not hold it for the database round trip
+        let snapshot = GAME_REGISTRY.read().await.get_game(game_id).map(GameSnapshot::from);
+        let Some(snapshot) = snapshot else {
+            return Ok(());
         };
+        self.game_repo.save_game(*game_id, &snapshot).await
+    }
+
+    /// Puts every saved game back into the registry, returns how many were restored
+    pub async fn restore_games(&self) -> Result<usize, DbErr> {
+        let games = self.game_repo.load_games().await?;
+        let mut registry = GAME_REGISTRY.write().await;
+        let count = games.len();
+        let mut members = Vec::with_capacity(count);
+        for (game_id, snapshot) in games {
+            let gh = GameHandler::restore(game_id, snapshot);
+            let users: Vec<Uuid> = gh.instance.players.keys().chain(&gh.instance.waiting).copied().collect();
+            members.push((game_id, users));
+            registry.insert(gh);
+        }
+        drop(registry);
+
+        // Games from before memberships were stored only know their players from the snapshot
+        for (game_id, users) in members {
+            self.game_repo.ensure_members(game_id, users).await?;
+        }
+        Ok(count)
+    }
+
+    /// The lobby as `session` sees it, open games first and then by name
+    pub async fn list_games(&self, session: &Session, filter: GameFilter) -> Vec<GameSummary> {
+        let mut games: Vec<GameSummary> = GAME_REGISTRY
+            .read()
+            .await
+            .games()
+            .map(|gh| gh.summary(&session.user))
+            .filter(|game| matches_filter(game, &filter))
+            .collect();
+
+        games.sort_by(|a, b| b.open_seats.cmp(&a.open_seats).then_with(|| a.name.cmp(&b.name)));
+        games
+    }
+
+    pub async fn watch_game(&self, game_id: &Uuid) -> Option<broadcast::Receiver<GameEvent>> {
+        GAME_REGISTRY.read().await.subscribe(game_id)
+    }
+}
+
+fn matches_filter(game: &GameSummary, filter: &GameFilter) -> bool {
+    game.result.is_some() == filter.finished
+        && (!filter.open_seats || game.open_seats > 0)
+        && (!filter.mine || game.joined)
+}
+
+#[cfg(test)]
+mod tests {
+    use common::protocol::GameResult;
+
+    use super::*;
+
+    fn two_seat_game(host: Uuid) -> GameHandler {
+        let mut gh = GameHandler::new();
+        gh.instance.seats = 2;
+        gh.instance.min_players = 2;
+        gh.try_join(host, None).unwrap();
         gh
-            .instance
-            .to_context_for(&session.user)
-            .ok_or("Cannot convert instance into context".to_string())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn open_games_are_listed_until_every_seat_is_taken() {
+        let host = Uuid::new_v4();
+        let stranger = Uuid::new_v4();
+        let open = GameFilter { open_seats: true, ..GameFilter::default() };
+        let mine = GameFilter { mine: true, ..GameFilter::default() };
+        let mut gh = two_seat_game(host);
+
+        let summary = gh.summary(&stranger);
+        assert_eq!(summary.open_seats, 1);
+        assert!(matches_filter(&summary, &open));
+        assert!(!matches_filter(&summary, &mine));
+        assert!(matches_filter(&gh.summary(&host), &mine));
+
+        gh.try_join(stranger, None).unwrap();
+        let summary = gh.summary(&stranger);
+        assert_eq!(summary.open_seats, 0);
+        assert!(!matches_filter(&summary, &open));
+        assert!(matches_filter(&summary, &GameFilter::default()));
+    }
+
+    #[test]
+    fn finished_games_are_only_listed_when_asked_for() {
+        let host = Uuid::new_v4();
+        let mut gh = two_seat_game(host);
+        gh.result = Some(GameResult::Draw { nations: Vec::new() });
+
+        let summary = gh.summary(&host);
+        assert!(!matches_filter(&summary, &GameFilter::default()));
+        assert!(matches_filter(&summary, &GameFilter { finished: true, ..GameFilter::default() }));
+    }
+}
@@ -0,0 +1,119 @@
+use std::fmt;
+use std::time::Duration;
+
+use common::hash::hash_password;
+use common::protocol::{ErrorCode, ErrorResponse, GameSettings, PhaseDeadlines};
+use sea_orm::DbErr;
+
+use crate::game::game_handler::GameHandler;
+
+/// The shortest deadline a game can be created with
+const MIN_DEADLINE: Duration = Duration::from_secs(60);
+/// The longest, four weeks
+const MAX_DEADLINE: Duration = Duration::from_secs(28 * 24 * 60 * 60);
+
+/// Why a game could not be created with the settings asked for
+#[derive(Debug, Clone)]
+pub enum SettingsError {
+    EmptyName,
+    PlayerCount { min: usize, max: usize, nations: usize },
+    DeadlineTooShort,
+    DeadlineTooLong,
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingsError::EmptyName => write!(f, "The game name cannot be blank"),
+            SettingsError::PlayerCount { min, max, nations } => write!(
+                f,
+                "Cannot start with {min} players and seat {max}, it needs at least 2 players and there are only {nations} nations",
+            ),
+            SettingsError::DeadlineTooShort => write!(f, "Every phase needs at least a minute"),
+            SettingsError::DeadlineTooLong => write!(f, "No phase can last longer than four weeks"),
+        }
+    }
+}
+
+impl std::error::Error for SettingsError {}
+
+impl From<SettingsError> for ErrorResponse {
+    fn from(e: SettingsError) -> Self {
+        ErrorResponse::new(ErrorCode::InvalidSettings, e.to_string())
+    }
+}
+
+/// Why `GameService::create_game` failed
+#[derive(Debug)]
+pub enum CreateError {
+    Settings(SettingsError),
+    Database(DbErr),
+}
+
+impl GameHandler {
+    /// A new game set up as `settings` asks, using `default_deadlines` if it gives none
+    pub fn configured(settings: &GameSettings, default_deadlines: PhaseDeadlines) -> Result<Self, SettingsError> {
+        let mut gh = GameHandler::new();
+
+        if let Some(name) = &settings.name {
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(SettingsError::EmptyName);
+            }
+            gh.name = name.to_string();
+        }
+
+        let nations = settings.variant.nations();
+        let max = settings.max_players.unwrap_or(nations);
+        let min = settings.min_players.unwrap_or(max);
+        if min < 2 || min > max || max > nations {
+            return Err(SettingsError::PlayerCount { min, max, nations });
+        }
+
+        let deadlines = match settings.deadlines {
+            Some(d) if [d.movement, d.retreat, d.build].iter().any(|length| *length < MIN_DEADLINE) => {
+                return Err(SettingsError::DeadlineTooShort);
+            }
+            Some(d) if [d.movement, d.retreat, d.build].iter().any(|length| *length > MAX_DEADLINE) => {
+                return Err(SettingsError::DeadlineTooLong);
+            }
+            Some(d) => d,
+            None => default_deadlines,
+        };
+
+        gh.instance.variant = settings.variant;
+        gh.instance.seats = max;
+        gh.instance.min_players = min;
+        gh.press = settings.press;
+        gh.assignment = settings.assignment;
+        if let Some(seed) = settings.seed {
+            gh.seed = seed;
+        }
+        gh.deadlines = deadlines;
+        gh.password_hash = settings.password.as_ref().map(hash_password);
+        Ok(gh)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_deadline(length: Duration) -> GameSettings {
+        let deadlines = PhaseDeadlines { movement: length, ..PhaseDeadlines::default() };
+        GameSettings { deadlines: Some(deadlines), ..GameSettings::default() }
+    }
+
+    #[test]
+    fn deadlines_have_to_be_at_least_a_minute() {
+        let res = GameHandler::configured(&with_deadline(Duration::from_secs(59)), PhaseDeadlines::default());
+        assert!(matches!(res, Err(SettingsError::DeadlineTooShort)));
+    }
+
+    #[test]
+    fn deadlines_cannot_run_past_four_weeks() {
+        let res = GameHandler::configured(&with_deadline(Duration::MAX), PhaseDeadlines::default());
+        assert!(matches!(res, Err(SettingsError::DeadlineTooLong)));
+        assert!(GameHandler::configured(&with_deadline(MAX_DEADLINE), PhaseDeadlines::default()).is_ok());
+    }
+}
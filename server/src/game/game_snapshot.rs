@@ -0,0 +1,193 @@
+use std::collections::{HashMap, HashSet};
+
+use common::context::{MapKind, SupplyCentreCount};
+use common::protocol::{GameResult, NationAssignment, PressMode};
+use diplomacy::geo::{ProvinceKey, RegionKey};
+use diplomacy::judge::{MappedBuildOrder, MappedMainOrder, MappedRetreatOrder};
+use diplomacy::{Nation, Time, UnitType};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use common::protocol::PhaseDeadlines;
+use crate::game::game_handler::{GameHandler, DEFAULT_CIVIL_DISORDER_AFTER};
+use crate::game::game_instance::PendingRetreat;
+use crate::order::order_collector::OrderCollector;
+
+type UserId = Uuid;
+
+/// Everything needed to rebuild a `GameHandler` after a restart, stored as JSON in the
+/// `state` column of the `games` table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameSnapshot {
+    pub players: HashMap<UserId, Nation>,
+    pub last_owners: HashMap<ProvinceKey, Nation>,
+    pub occupiers: HashMap<ProvinceKey, Nation>,
+    pub units: HashMap<Nation, HashSet<(UnitType, RegionKey)>>,
+    pub pending_retreats: Vec<PendingRetreat>,
+    #[serde(default)]
+    pub dislodging_orders: Vec<MappedMainOrder>,
+    #[serde(default)]
+    pub supply_centre_history: Vec<SupplyCentreCount>,
+    #[serde(default)]
+    pub missed_phases: HashMap<Nation, u32>,
+    #[serde(default)]
+    pub civil_disorder: HashSet<Nation>,
+    pub time: Time,
+    pub main_orders: CollectedOrders<MappedMainOrder>,
+    pub retreat_orders: CollectedOrders<MappedRetreatOrder>,
+    pub build_orders: CollectedOrders<MappedBuildOrder>,
+    #[serde(default)]
+    pub deadlines: PhaseDeadlines,
+    #[serde(default, with = "time::serde::timestamp::option")]
+    pub deadline: Option<OffsetDateTime>,
+    #[serde(default = "default_civil_disorder_after")]
+    pub civil_disorder_after: u32,
+    #[serde(default)]
+    pub draw_votes: HashSet<UserId>,
+    #[serde(default)]
+    pub result: Option<GameResult>,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub variant: MapKind,
+    #[serde(default)]
+    pub press: PressMode,
+    #[serde(default)]
+    pub password_hash: Option<String>,
+    #[serde(default = "default_seats")]
+    pub seats: usize,
+    #[serde(default = "default_seats")]
+    pub min_players: usize,
+    #[serde(default)]
+    pub waiting: Vec<UserId>,
+    /// Missing for games saved before starting was explicit, those started with enough players
+    #[serde(default)]
+    pub started: Option<bool>,
+    #[serde(default)]
+    pub assignment: NationAssignment,
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(default)]
+    pub preferences: HashMap<UserId, Vec<Nation>>,
+    #[serde(default)]
+    pub host: Option<UserId>,
+    #[serde(default)]
+    pub spectators: HashSet<UserId>,
+}
+
+fn default_seats() -> usize {
+    MapKind::Standard.nations()
+}
+
+fn default_civil_disorder_after() -> u32 {
+    DEFAULT_CIVIL_DISORDER_AFTER
+}
+
+/// The orders handed in so far for one kind of phase and who has said they are done
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectedOrders<O> {
+    pub orders: HashMap<UserId, Vec<O>>,
+    pub ready: HashSet<UserId>,
+}
+
+impl<O: Clone> CollectedOrders<O> {
+    fn collect<C: OrderCollector<O>>(collector: &C, orders: &HashMap<UserId, Vec<O>>, players: &HashMap<UserId, Nation>) -> Self {
+        Self {
+            orders: orders.clone(),
+            ready: players.keys().filter(|user| collector.is_player_ready(user)).copied().collect(),
+        }
+    }
+}
+
+fn mark_all_ready<O, C: OrderCollector<O>>(collector: &mut C, ready: HashSet<UserId>) {
+    for user in ready {
+        collector.mark_ready(user);
+    }
+}
+
+impl From<&GameHandler> for GameSnapshot {
+    fn from(gh: &GameHandler) -> Self {
+        let instance = &gh.instance;
+        Self {
+            players: instance.players.clone(),
+            last_owners: instance.last_owners.clone(),
+            occupiers: instance.occupiers.clone(),
+            units: instance.units.clone(),
+            pending_retreats: instance.pending_retreats.clone(),
+            dislodging_orders: instance.dislodging_orders.clone(),
+            supply_centre_history: instance.supply_centre_history.clone(),
+            missed_phases: instance.missed_phases.clone(),
+            civil_disorder: instance.civil_disorder.clone(),
+            time: instance.time().clone(),
+            main_orders: CollectedOrders::collect(&gh.main_orders, &gh.main_orders.player_orders, &instance.players),
+            retreat_orders: CollectedOrders::collect(&gh.retreat_orders, &gh.retreat_orders.player_orders, &instance.players),
+            build_orders: CollectedOrders::collect(&gh.build_orders, &gh.build_orders.player_orders, &instance.players),
+            deadlines: gh.deadlines,
+            deadline: gh.deadline,
+            civil_disorder_after: gh.civil_disorder_after,
+            draw_votes: gh.draw_votes.clone(),
+            result: gh.result.clone(),
+            name: gh.name.clone(),
+            variant: instance.variant,
+            press: gh.press,
+            password_hash: gh.password_hash.clone(),
+            seats: instance.seats,
+            min_players: instance.min_players,
+            waiting: instance.waiting.clone(),
+            started: Some(instance.started),
+            assignment: gh.assignment,
+            seed: gh.seed,
+            preferences: gh.preferences.clone(),
+            host: gh.host,
+            spectators: gh.spectators.clone(),
+        }
+    }
+}
+
+impl GameHandler {
+    /// Rebuilds a game that was saved with `GameSnapshot::from`
+    pub fn restore(id: Uuid, snapshot: GameSnapshot) -> Self {
+        let mut gh = GameHandler::new();
+        gh.id = id;
+
+        let instance = &mut gh.instance;
+        instance.started = snapshot.started.unwrap_or(snapshot.players.len() >= snapshot.min_players);
+        instance.players = snapshot.players;
+        instance.waiting = snapshot.waiting;
+        instance.seats = snapshot.seats;
+        instance.min_players = snapshot.min_players;
+        instance.variant = snapshot.variant;
+        instance.last_owners = snapshot.last_owners;
+        instance.occupiers = snapshot.occupiers;
+        instance.units = snapshot.units;
+        instance.pending_retreats = snapshot.pending_retreats;
+        instance.dislodging_orders = snapshot.dislodging_orders;
+        instance.supply_centre_history = snapshot.supply_centre_history;
+        instance.missed_phases = snapshot.missed_phases;
+        instance.civil_disorder = snapshot.civil_disorder;
+        instance.resume_at(snapshot.time);
+
+        gh.main_orders.player_orders = snapshot.main_orders.orders;
+        mark_all_ready(&mut gh.main_orders, snapshot.main_orders.ready);
+        gh.retreat_orders.player_orders = snapshot.retreat_orders.orders;
+        mark_all_ready(&mut gh.retreat_orders, snapshot.retreat_orders.ready);
+        gh.build_orders.player_orders = snapshot.build_orders.orders;
+        mark_all_ready(&mut gh.build_orders, snapshot.build_orders.ready);
+        gh.deadlines = snapshot.deadlines;
+        gh.deadline = snapshot.deadline;
+        gh.civil_disorder_after = snapshot.civil_disorder_after;
+        gh.draw_votes = snapshot.draw_votes;
+        gh.result = snapshot.result;
+        gh.name = snapshot.name;
+        gh.press = snapshot.press;
+        gh.password_hash = snapshot.password_hash;
+        gh.assignment = snapshot.assignment;
+        gh.seed = snapshot.seed;
+        gh.preferences = snapshot.preferences;
+        gh.host = snapshot.host;
+        gh.spectators = snapshot.spectators;
+
+        gh
+    }
+}
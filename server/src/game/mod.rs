@@ -2,4 +2,9 @@ pub mod game_registry;
 pub mod game_instance;
 pub mod game_handler;
 pub mod game_service;
-pub mod game_repository;
\ No newline at end of file
+pub mod game_repository;
+pub mod game_snapshot;
+pub mod phase_sequencer;
+pub mod deadline;
+pub mod game_settings;
+pub mod nation_assignment;
\ No newline at end of file
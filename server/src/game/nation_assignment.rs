@@ -0,0 +1,113 @@
+use std::collections::{HashMap, HashSet};
+
+use common::protocol::NationAssignment;
+use diplomacy::Nation;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use uuid::Uuid;
+
+type UserId = Uuid;
+
+/// Hands each player one of the `free` nations when the game starts.
+///
+/// With `Random` the nations are shuffled and dealt out. With `Preferences` players pick in
+/// a random order, each taking the highest nation on their list nobody has taken yet, and
+/// anyone whose list has run out is dealt one of the nations left over at random.
+pub fn assign(
+    mode: NationAssignment,
+    players: &[UserId],
+    free: &[Nation],
+    preferences: &HashMap<UserId, Vec<Nation>>,
+    rng: &mut StdRng,
+) -> HashMap<UserId, Nation> {
+    // Sorted first so the same seed always gives the same game
+    let mut free = free.to_vec();
+    free.sort_by_key(|nation| nation.to_string());
+    free.shuffle(rng);
+
+    let mut order = players.to_vec();
+    order.sort();
+    order.shuffle(rng);
+
+    let mut assigned = HashMap::with_capacity(order.len());
+    if mode == NationAssignment::Preferences {
+        let mut taken = HashSet::new();
+        for user in &order {
+            let choice = preferences
+                .get(user)
+                .into_iter()
+                .flatten()
+                .find(|nation| free.contains(nation) && !taken.contains(*nation));
+            if let Some(nation) = choice {
+                taken.insert(nation.clone());
+                assigned.insert(*user, nation.clone());
+            }
+        }
+        free.retain(|nation| !taken.contains(nation));
+    }
+
+    let unassigned: Vec<UserId> = order.into_iter().filter(|user| !assigned.contains_key(user)).collect();
+    assigned.extend(unassigned.into_iter().zip(free));
+    assigned
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    fn nations(names: &[&str]) -> Vec<Nation> {
+        names.iter().map(|name| Nation::from(*name)).collect()
+    }
+
+    fn players(count: usize) -> Vec<UserId> {
+        (0..count).map(|_| Uuid::new_v4()).collect()
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_assignment() {
+        let players = players(4);
+        let free = nations(&["AUS", "ENG", "FRA", "GER", "ITA", "RUS", "TUR"]);
+        let preferences = HashMap::from([(players[0], nations(&["FRA"])), (players[1], nations(&["FRA", "ENG"]))]);
+
+        for mode in [NationAssignment::Random, NationAssignment::Preferences] {
+            let first = assign(mode, &players, &free, &preferences, &mut StdRng::seed_from_u64(7));
+            // The order players joined in and nations were listed in makes no difference
+            let mut reordered_players = players.clone();
+            reordered_players.reverse();
+            let mut reordered_free = free.clone();
+            reordered_free.reverse();
+            let second = assign(mode, &reordered_players, &reordered_free, &preferences, &mut StdRng::seed_from_u64(7));
+            assert_eq!(first, second);
+        }
+    }
+
+    #[test]
+    fn everyone_gets_a_different_nation() {
+        let players = players(7);
+        let free = nations(&["AUS", "ENG", "FRA", "GER", "ITA", "RUS", "TUR"]);
+        let assigned = assign(NationAssignment::Random, &players, &free, &HashMap::new(), &mut StdRng::seed_from_u64(1));
+        let handed_out: HashSet<&Nation> = assigned.values().collect();
+        assert_eq!(assigned.len(), 7);
+        assert_eq!(handed_out.len(), 7);
+    }
+
+    #[test]
+    fn first_preferences_are_honoured_without_conflicts() {
+        let players = players(3);
+        let free = nations(&["AUS", "ENG", "FRA", "GER", "ITA", "RUS", "TUR"]);
+        let preferences = HashMap::from([
+            (players[0], nations(&["TUR", "ENG"])),
+            (players[1], nations(&["ENG", "TUR"])),
+            (players[2], nations(&["ITA"])),
+        ]);
+
+        for seed in 0..20 {
+            let assigned = assign(NationAssignment::Preferences, &players, &free, &preferences, &mut StdRng::seed_from_u64(seed));
+            assert_eq!(assigned[&players[0]], Nation::from("TUR"));
+            assert_eq!(assigned[&players[1]], Nation::from("ENG"));
+            assert_eq!(assigned[&players[2]], Nation::from("ITA"));
+        }
+    }
+}
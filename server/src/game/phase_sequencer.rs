@@ -0,0 +1,43 @@
+use diplomacy::{Phase, Season, Time};
+
+/// The phase that follows `time` in a standard game:
+/// Spring Movement, Spring Retreat, Fall Movement, Fall Retreat, Winter Build, then the next year.
+pub fn following(time: &Time) -> Time {
+    let year = time.year();
+    match (time.season(), time.phase()) {
+        (Season::Spring, Phase::Main) => Time::new(Season::Spring, year, Phase::Retreat),
+        (Season::Spring, _) => Time::new(Season::Fall, year, Phase::Main),
+        (Season::Fall, Phase::Main) => Time::new(Season::Fall, year, Phase::Retreat),
+        (Season::Fall, _) => Time::new(Season::Winter, year, Phase::Build),
+        (Season::Winter, _) | (Season::Summer, _) => Time::new(Season::Spring, year + 1, Phase::Main),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spring_movement_is_followed_by_its_retreats() {
+        let next = following(&Time::new(Season::Spring, 1901, Phase::Main));
+        assert_eq!(next, Time::new(Season::Spring, 1901, Phase::Retreat));
+    }
+
+    #[test]
+    fn spring_moves_on_to_fall() {
+        let next = following(&Time::new(Season::Spring, 1901, Phase::Retreat));
+        assert_eq!(next, Time::new(Season::Fall, 1901, Phase::Main));
+    }
+
+    #[test]
+    fn fall_moves_on_to_winter_builds() {
+        let next = following(&Time::new(Season::Fall, 1901, Phase::Retreat));
+        assert_eq!(next, Time::new(Season::Winter, 1901, Phase::Build));
+    }
+
+    #[test]
+    fn winter_starts_the_next_year() {
+        let next = following(&Time::new(Season::Winter, 1901, Phase::Build));
+        assert_eq!(next, Time::new(Season::Spring, 1902, Phase::Main));
+    }
+}
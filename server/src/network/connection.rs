@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use common::protocol::{read_frame, write_frame, ErrorCode, ErrorResponse, FrameError, GameEvent, Request, Response};
+use tokio::net::TcpStream;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tokio::task::AbortHandle;
+use uuid::Uuid;
+
+use crate::auth::connections_manager::ConnectionsManager;
+
+pub type ConnectionId = Uuid;
+type SessionId = Uuid;
+
+/// What the server knows about a live client socket
+///
+/// Responses are never written to the socket directly, they are queued on `outbound`
+/// so that anything holding the handle (not just the request loop) can talk to the client.
+#[derive(Debug)]
+pub struct ConnectionHandle {
+    pub session: Option<SessionId>,
+    pub outbound: UnboundedSender<Response>,
+    watchers: Vec<AbortHandle>,
+}
+
+impl ConnectionHandle {
+    pub fn new(outbound: UnboundedSender<Response>) -> Self {
+        Self { session: None, outbound, watchers: Vec::new() }
+    }
+
+    /// Pushes every event from `events` to this client until either side goes away
+    pub fn watch(&mut self, mut events: broadcast::Receiver<GameEvent>) {
+        let outbound = self.outbound.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        if outbound.send(Response::Event(event)).is_err() {
+                            break;
+                        }
+                    }
+                    Err(RecvError::Lagged(missed)) => {
+                        eprintln!("Watcher fell behind and missed {missed} events");
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+        self.watchers.push(task.abort_handle());
+    }
+}
+
+impl Drop for ConnectionHandle {
+    fn drop(&mut self) {
+        for watcher in &self.watchers {
+            watcher.abort();
+        }
+    }
+}
+
+/// Serves every request a client sends over one socket until it disconnects
+pub async fn handle_connection(stream: TcpStream, cm: Arc<ConnectionsManager>) {
+    let (mut reader, mut writer) = stream.into_split();
+    let (outbound, mut queued) = mpsc::unbounded_channel::<Response>();
+
+    let writer_task = tokio::spawn(async move {
+        while let Some(response) = queued.recv().await {
+            if let Err(e) = write_frame(&mut writer, &response).await {
+                eprintln!("Failed to write to client: {e}");
+                break;
+            }
+        }
+    });
+
+    let connection_id = cm.register_connection(ConnectionHandle::new(outbound.clone())).await;
+
+    loop {
+        let request: Request = match read_frame(&mut reader).await {
+            Ok(Some(request)) => request,
+            Ok(None) => {
+                println!("Client has disconnected");
+                break;
+            }
+            // The frame itself arrived intact, so the stream is still in sync and we can carry on
+            Err(FrameError::Malformed(e)) => {
+                eprintln!("Malformed request: {e}");
+                let error = ErrorResponse::new(ErrorCode::MalformedRequest, format!("Could not understand the request: {e}"));
+                if outbound.send(Response::Error(error)).is_err() {
+                    break;
+                }
+                continue;
+            }
+            Err(e) => {
+                eprintln!("Client error: {e}");
+                break;
+            }
+        };
+
+        let response = cm.handle_request(connection_id, request).await;
+        if outbound.send(response).is_err() {
+            break;
+        }
+    }
+
+    cm.disconnect(&connection_id).await;
+    drop(outbound);
+    let _ = writer_task.await;
+}
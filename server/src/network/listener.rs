@@ -0,0 +1,17 @@
+use std::sync::Arc;
+
+use tokio::net::TcpListener;
+
+use crate::auth::connections_manager::ConnectionsManager;
+use crate::network::connection::handle_connection;
+
+/// Accepts clients forever, giving each socket its own task
+pub async fn listen(addr: &str, cm: Arc<ConnectionsManager>) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    println!("Server listening on {addr}");
+
+    loop {
+        let (socket, _) = listener.accept().await?;
+        tokio::spawn(handle_connection(socket, cm.clone()));
+    }
+}
@@ -1,7 +1,7 @@
 use std::{collections::{HashMap, HashSet}, error::Error};
 
 use uuid::Uuid;
-use diplomacy::{Command, Nation, Order, Phase, UnitPosition, UnitType, geo::RegionKey, judge::{MappedBuildOrder, MappedMainOrder, MappedRetreatOrder, build::WorldState, retreat::Destinations}, order};
+use diplomacy::{Command, Nation, Phase, UnitType, geo::{ProvinceKey, RegionKey}, order::{BuildCommand, MainCommand}, judge::{MappedBuildOrder, MappedMainOrder, MappedRetreatOrder}};
 
 use crate::{data::game, game::{game_handler::OrderError, game_instance::{self, GameInstance, PendingRetreat}}};
 
@@ -10,12 +10,21 @@ use crate::{data::game, game::{game_handler::OrderError, game_instance::{self, G
 pub trait OrderCollector<O> {
     fn submit_order(&mut self, game_instance: &GameInstance, user: Uuid, orders: Vec<O>) -> Result<Uuid, OrderError>;
     fn mark_ready(&mut self, user: Uuid);
+    fn unmark_ready(&mut self, user: Uuid);
+    fn has_orders(&self, user: &Uuid) -> bool;
     fn is_player_ready(&self, user: &Uuid) -> bool;
-    fn all_players_ready(&self) -> bool;
+    /// Whether every player still in the game is done with this phase
+    fn all_players_ready(&self, game_instance: &GameInstance) -> bool;
     fn snapshot(&self) -> Option<String>;
     fn clear(&mut self);
 }
 
+/// Readiness only counts once the game has started, after that players whose nation
+/// has been eliminated or fallen into civil disorder are no longer waited on
+fn everyone_ready(game_instance: &GameInstance, is_ready: impl Fn(&Uuid) -> bool) -> bool {
+    game_instance.has_started() && game_instance.attending_players().all(is_ready)
+}
+
 pub fn get_order_positions(orders: &Vec<MappedMainOrder>) -> HashSet<(UnitType, RegionKey)> {
     orders
         .into_iter()
@@ -46,30 +55,61 @@ impl MainOrderCollector {
             .collect()
     }
 
+    /// Every order given, with a hold for each unit nobody gave an order to
+    pub fn orders_with_holds(&self, game_instance: &GameInstance) -> Vec<MappedMainOrder> {
+        let mut orders = self.all_orders();
+        let ordered: HashSet<RegionKey> = orders.iter().map(|o| o.region.clone()).collect();
+        for (nation, units) in &game_instance.units {
+            for (unit_type, region) in units.iter().filter(|(_, region)| !ordered.contains(region)) {
+                orders.push(MappedMainOrder::new(nation.clone(), *unit_type, region.clone(), MainCommand::Hold));
+            }
+        }
+        orders
+    }
+
+    /// The player's units that will hold because they have not been given an order
+    pub fn units_without_orders(&self, game_instance: &GameInstance, user: &Uuid) -> Vec<(UnitType, RegionKey)> {
+        let ordered = self.player_orders.get(user).map(get_order_positions).unwrap_or_default();
+        game_instance.find_player_units(user).difference(&ordered).cloned().collect()
+    }
+
+    /// Auto-ready players whose nation has no units left to order
+    pub fn pre_add_readiness(&mut self, game_instance: &GameInstance) {
+        for user in game_instance.players.keys() {
+            if game_instance.find_player_units(user).is_empty() {
+                self.ready_players.insert(*user, true);
+            }
+        }
+    }
 }
+
 impl OrderCollector<MappedMainOrder> for MainOrderCollector {
     fn submit_order(&mut self, game_instance: &GameInstance, user: Uuid, orders: Vec<MappedMainOrder>) -> Result<Uuid, OrderError> {
         // Must be same phase
         // println!("[DEBUG] This is the current stuff in the game_instabnce: {:?}", game_instance);
-        if game_instance.phase != Phase::Main {
+        if game_instance.phase() != Phase::Main {
             return Err(OrderError::WrongPhase)
         }
+        let nation = game_instance.players.get(&user).ok_or(OrderError::NotInGame)?;
+        if orders.iter().any(|o| &o.nation != nation) {
+            return Err(OrderError::InvalidOrderPositions)
+        }
 
-        let nation = game_instance.players.get(&user).ok_or(OrderError::WrongPhase)?;
-        let unit_count: usize = game_instance.unit_count(nation).into();
-        let order_count = orders.len();
-
-        if unit_count != order_count {
-            return Err(OrderError::InvalidOrderCount { expected: unit_count, found: order_count })
+        // An empty submission would count as having given orders without ordering anything
+        if orders.is_empty() {
+            return Err(OrderError::IncorrectOrderCount)
         }
 
-        // Must be their ALL of their units
-        if game_instance.find_player_units(&user) != get_order_positions(&orders) {
+        // Any of their units may be ordered, but only once per submission
+        let positions = get_order_positions(&orders);
+        if positions.len() != orders.len() || !positions.is_subset(&game_instance.find_player_units(&user)) {
             return Err(OrderError::InvalidOrderPositions)
         }
-        
-        self.player_orders.insert(user, orders);
-        self.mark_ready(user);
+
+        // New orders replace whatever those units were told before
+        let player_orders = self.player_orders.entry(user).or_default();
+        player_orders.retain(|o| !positions.contains(&(o.unit_type, o.region.clone())));
+        player_orders.extend(orders);
         Ok(user)
     }
 
@@ -77,22 +117,20 @@ impl OrderCollector<MappedMainOrder> for MainOrderCollector {
         self.ready_players.insert(user, true);
     }
 
+    fn unmark_ready(&mut self, user: Uuid) {
+        self.ready_players.remove(&user);
+    }
+
+    fn has_orders(&self, user: &Uuid) -> bool {
+        self.player_orders.contains_key(user)
+    }
+
     fn is_player_ready(&self, user: &Uuid) -> bool {
         self.ready_players.get(user).unwrap_or(&false).clone()
     }
 
-    fn all_players_ready(&self) -> bool {
-        println!(
-            "[DEBUG] ready_players = {:?}, players = {:?}",
-            self.ready_players.keys(),
-            self.ready_players.values()
-        );
-        if self.ready_players.len() < 7 {
-            return false;
-        } else {
-            println!("[DEBUG] I will return true");
-            return true;
-        }
+    fn all_players_ready(&self, game_instance: &GameInstance) -> bool {
+        everyone_ready(game_instance, |user| self.is_player_ready(user))
     }
 
     fn snapshot(&self) -> Option<String> {
@@ -149,13 +187,16 @@ impl OrderCollector<MappedRetreatOrder> for RetreatOrderCollector {
         user: Uuid,
         orders: Vec<MappedRetreatOrder>,
     ) -> Result<Uuid, OrderError> {
-        if game_instance.phase != Phase::Retreat {
+        if game_instance.phase() != Phase::Retreat {
             return Err(OrderError::WrongPhase);
         }
 
-        let nation = game_instance.players.get(&user).ok_or(OrderError::WrongPhase)?;
+        let nation = game_instance.players.get(&user).ok_or(OrderError::NotInGame)?;
+        if orders.iter().any(|o| &o.nation != nation) {
+            return Err(OrderError::InvalidOrderPositions);
+        }
 
-        // All retreats this player MUST resolve
+        // Every dislodged unit has to either retreat or disband
         let required_retreats: Vec<&PendingRetreat> = game_instance
             .pending_retreats
             .iter()
@@ -195,7 +236,6 @@ impl OrderCollector<MappedRetreatOrder> for RetreatOrderCollector {
         }
 
         self.player_orders.insert(user, orders);
-        self.mark_ready(user);
         Ok(user)
     }
 
@@ -203,12 +243,20 @@ impl OrderCollector<MappedRetreatOrder> for RetreatOrderCollector {
         self.ready_players.insert(user);
     }
 
+    fn unmark_ready(&mut self, user: Uuid) {
+        self.ready_players.remove(&user);
+    }
+
+    fn has_orders(&self, user: &Uuid) -> bool {
+        self.player_orders.contains_key(user)
+    }
+
     fn is_player_ready(&self, user: &Uuid) -> bool {
         self.ready_players.contains(user)
     }
 
-    fn all_players_ready(&self) -> bool {
-        self.ready_players.len() == 7
+    fn all_players_ready(&self, game_instance: &GameInstance) -> bool {
+        everyone_ready(game_instance, |user| self.is_player_ready(user))
     }
 
     fn snapshot(&self) -> Option<String> {
@@ -239,16 +287,16 @@ impl BuildOrderCollector {
         }
     }
 
-    /// Auto-ready players with zero builds
+    /// Auto-ready players whose nation neither builds nor disbands this winter
     pub fn pre_add_readiness(&mut self, game_instance: &GameInstance) {
-    //     let auto_ready_users: HashSet<Uuid> = game_instance
-    //         .players
-    //         .iter()
-    //         .filter(|(user, _)| game_instance.build_count(user) == 0)
-    //         .map(|(user, _)| *user)
-    //         .collect();
+        let auto_ready_users: HashSet<Uuid> = game_instance
+            .players
+            .iter()
+            .filter(|(_, nation)| game_instance.adjustment(nation) == 0)
+            .map(|(user, _)| *user)
+            .collect();
 
-    //     self.ready_players.extend(auto_ready_users);
+        self.ready_players.extend(auto_ready_users);
     }
 
     pub fn all_orders(&self) -> Vec<MappedBuildOrder> {
@@ -267,36 +315,51 @@ impl OrderCollector<MappedBuildOrder> for BuildOrderCollector {
         user: Uuid,
         orders: Vec<MappedBuildOrder>,
     ) -> Result<Uuid, OrderError> {
+        if game_instance.phase() != Phase::Build {
+            return Err(OrderError::WrongPhase);
+        }
 
-        // if game_instance.phase != Phase::Build {
-        //     return Err(OrderError::WrongPhase);
-        // }
-
-        // let build_count = game_instance.build_count(&user);
-        // let order_count = orders.len();
-
-        // if build_count != order_count {
-        //     return Err(OrderError::InvalidOrderCount {
-        //         expected: build_count,
-        //         found: order_count,
-        //     });
-        // }
-
-        // // Allowed build locations for this nation
-        // let allowed_builds: HashSet<RegionKey> =
-        //     game_instance.allowed_build_regions(&user);
+        let nation = game_instance.players.get(&user).ok_or(OrderError::NotInGame)?;
+        if orders.iter().any(|o| &o.nation != nation) {
+            return Err(OrderError::InvalidOrderPositions);
+        }
 
-        // let ordered_regions: HashSet<RegionKey> = orders
-        //     .iter()
-        //     .map(|o| o.region.clone())
-        //     .collect();
+        // Positive means builds are owed, negative means disbands are
+        let adjustment = game_instance.adjustment(nation);
+        let allowed = adjustment.unsigned_abs() as usize;
+        let command = if adjustment > 0 { BuildCommand::Build } else { BuildCommand::Disband };
+        if orders.iter().any(|o| o.command != command) {
+            return Err(OrderError::WrongAdjustment);
+        }
 
-        // if !ordered_regions.is_subset(&allowed_builds) {
-        //     return Err(OrderError::InvalidOrderPositions);
-        // }
+        let provinces: HashSet<&ProvinceKey> = orders.iter().map(|o| o.region.province()).collect();
+        match command {
+            // Builds left unused are waived
+            BuildCommand::Build => {
+                if orders.len() > allowed {
+                    return Err(OrderError::InvalidOrderCount { expected: allowed, found: orders.len() });
+                }
+                if provinces.len() != orders.len()
+                    || !orders.iter().all(|o| game_instance.can_build_at(nation, o.unit_type, &o.region))
+                {
+                    return Err(OrderError::InvalidBuildSite);
+                }
+            }
+            // Every disband owed has to be given
+            BuildCommand::Disband => {
+                if orders.len() != allowed {
+                    return Err(OrderError::InvalidOrderCount { expected: allowed, found: orders.len() });
+                }
+                let units = game_instance.find_player_units(&user);
+                if provinces.len() != orders.len()
+                    || !orders.iter().all(|o| units.contains(&(o.unit_type, o.region.clone())))
+                {
+                    return Err(OrderError::InvalidOrderPositions);
+                }
+            }
+        }
 
-        // self.player_orders.insert(user, orders);
-        // self.mark_ready(user);
+        self.player_orders.insert(user, orders);
         Ok(user)
     }
 
@@ -304,12 +367,20 @@ impl OrderCollector<MappedBuildOrder> for BuildOrderCollector {
         self.ready_players.insert(user);
     }
 
+    fn unmark_ready(&mut self, user: Uuid) {
+        self.ready_players.remove(&user);
+    }
+
+    fn has_orders(&self, user: &Uuid) -> bool {
+        self.player_orders.contains_key(user)
+    }
+
     fn is_player_ready(&self, user: &Uuid) -> bool {
         self.ready_players.contains(user)
     }
 
-    fn all_players_ready(&self) -> bool {
-        self.ready_players.len() == 7
+    fn all_players_ready(&self, game_instance: &GameInstance) -> bool {
+        everyone_ready(game_instance, |user| self.is_player_ready(user))
     }
 
     fn snapshot(&self) -> Option<String> {
@@ -322,4 +393,179 @@ impl OrderCollector<MappedBuildOrder> for BuildOrderCollector {
         self.player_orders.clear();
         self.ready_players.clear();
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use diplomacy::order::RetreatCommand;
+    use diplomacy::{Season, Time};
+
+    use super::*;
+
+    fn region(name: &str) -> RegionKey {
+        RegionKey::from_str(name).unwrap()
+    }
+
+    fn build(nation: &str, unit_type: UnitType, at: &str) -> MappedBuildOrder {
+        MappedBuildOrder::new(Nation::from(nation), unit_type, region(at), BuildCommand::Build)
+    }
+
+    fn disband(nation: &str, unit_type: UnitType, at: &str) -> MappedBuildOrder {
+        MappedBuildOrder::new(Nation::from(nation), unit_type, region(at), BuildCommand::Disband)
+    }
+
+    /// A winter where Germany is playing and has `extra` more units than centres,
+    /// or is owed builds when it is negative
+    fn winter(extra: i32) -> (GameInstance, Uuid) {
+        let mut game = GameInstance::new();
+        let user = Uuid::new_v4();
+        let germany = Nation::from("GER");
+        game.players.insert(user, germany.clone());
+        game.resume_at(Time::new(Season::Winter, 1901, Phase::Build));
+
+        let units = game.units.get_mut(&germany).unwrap();
+        if extra > 0 {
+            units.insert((UnitType::Army, region("boh")));
+        } else if extra < 0 {
+            units.remove(&(UnitType::Army, region("ber")));
+        }
+        (game, user)
+    }
+
+    fn main_orders(orders: &[&str]) -> Vec<MappedMainOrder> {
+        orders.iter().map(|order| order.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn later_orders_replace_earlier_ones_for_the_same_unit() {
+        let mut game = GameInstance::new();
+        let user = Uuid::new_v4();
+        game.players.insert(user, Nation::from("GER"));
+        let mut collector = MainOrderCollector::new();
+        collector.submit_order(&game, user, main_orders(&["GER: A ber -> sil", "GER: F kie -> den"])).unwrap();
+        collector.submit_order(&game, user, main_orders(&["GER: A ber holds"])).unwrap();
+
+        let mut orders: Vec<String> = collector.all_orders().iter().map(|o| o.to_string()).collect();
+        orders.sort();
+        assert_eq!(orders, ["GER: A ber holds", "GER: F kie -> den"]);
+    }
+
+    #[test]
+    fn moves_for_another_nation_are_refused() {
+        let mut game = GameInstance::new();
+        let user = Uuid::new_v4();
+        game.players.insert(user, Nation::from("GER"));
+        let res = MainOrderCollector::new().submit_order(&game, user, main_orders(&["FRA: A ber -> mun"]));
+        assert!(matches!(res, Err(OrderError::InvalidOrderPositions)));
+    }
+
+    #[test]
+    fn empty_main_submissions_do_not_count_as_orders() {
+        let mut game = GameInstance::new();
+        let user = Uuid::new_v4();
+        game.players.insert(user, Nation::from("GER"));
+        let mut collector = MainOrderCollector::new();
+        let res = collector.submit_order(&game, user, Vec::new());
+        assert!(matches!(res, Err(OrderError::IncorrectOrderCount)));
+        assert!(!collector.has_orders(&user));
+    }
+
+    /// A spring retreat where Germany has to move its army out of Munich
+    fn retreat_from_munich() -> (GameInstance, Uuid) {
+        let mut game = GameInstance::new();
+        let user = Uuid::new_v4();
+        game.players.insert(user, Nation::from("GER"));
+        game.resume_at(Time::new(Season::Spring, 1901, Phase::Retreat));
+        game.pending_retreats.push(PendingRetreat {
+            nation: Nation::from("GER"),
+            unit_type: UnitType::Army,
+            from: region("mun"),
+            options: HashSet::from([region("boh"), region("ruh")]),
+        });
+        (game, user)
+    }
+
+    fn retreat(nation: &str, from: &str, to: &str) -> MappedRetreatOrder {
+        MappedRetreatOrder::new(Nation::from(nation), UnitType::Army, region(from), RetreatCommand::Move(region(to)))
+    }
+
+    #[test]
+    fn retreats_to_an_open_province_are_accepted() {
+        let (game, user) = retreat_from_munich();
+        let res = RetreatOrderCollector::new().submit_order(&game, user, vec![retreat("GER", "mun", "boh")]);
+        assert!(res.is_ok());
+    }
+
+    #[test]
+    fn retreats_for_another_nation_are_refused() {
+        let (game, user) = retreat_from_munich();
+        let res = RetreatOrderCollector::new().submit_order(&game, user, vec![retreat("FRA", "mun", "boh")]);
+        assert!(matches!(res, Err(OrderError::InvalidOrderPositions)));
+    }
+
+    #[test]
+    fn builds_on_an_empty_home_centre_are_accepted() {
+        let (game, user) = winter(-1);
+        let res = BuildOrderCollector::new().submit_order(&game, user, vec![build("GER", UnitType::Army, "ber")]);
+        assert!(res.is_ok());
+    }
+
+    #[test]
+    fn disbands_are_refused_when_builds_are_owed() {
+        let (game, user) = winter(-1);
+        let res = BuildOrderCollector::new().submit_order(&game, user, vec![disband("GER", UnitType::Army, "mun")]);
+        assert!(matches!(res, Err(OrderError::WrongAdjustment)));
+    }
+
+    #[test]
+    fn builds_are_refused_when_disbands_are_owed() {
+        let (game, user) = winter(1);
+        let res = BuildOrderCollector::new().submit_order(&game, user, vec![build("GER", UnitType::Army, "ber")]);
+        assert!(matches!(res, Err(OrderError::WrongAdjustment)));
+    }
+
+    #[test]
+    fn more_builds_than_owed_are_refused() {
+        let (game, user) = winter(-1);
+        let orders = vec![build("GER", UnitType::Army, "ber"), build("GER", UnitType::Army, "mun")];
+        let res = BuildOrderCollector::new().submit_order(&game, user, orders);
+        assert!(matches!(res, Err(OrderError::InvalidOrderCount { expected: 1, found: 2 })));
+    }
+
+    #[test]
+    fn every_disband_owed_has_to_be_given() {
+        let (game, user) = winter(1);
+        let res = BuildOrderCollector::new().submit_order(&game, user, Vec::new());
+        assert!(matches!(res, Err(OrderError::InvalidOrderCount { expected: 1, found: 0 })));
+    }
+
+    #[test]
+    fn builds_away_from_home_centres_are_refused() {
+        let (game, user) = winter(-1);
+        let res = BuildOrderCollector::new().submit_order(&game, user, vec![build("GER", UnitType::Army, "sil")]);
+        assert!(matches!(res, Err(OrderError::InvalidBuildSite)));
+    }
+
+    #[test]
+    fn builds_on_an_occupied_centre_are_refused() {
+        let (mut game, user) = winter(-1);
+        game.occupiers.insert(region("ber").province().clone(), Nation::from("RUS"));
+        let res = BuildOrderCollector::new().submit_order(&game, user, vec![build("GER", UnitType::Army, "ber")]);
+        assert!(matches!(res, Err(OrderError::InvalidBuildSite)));
+    }
+
+    #[test]
+    fn disbanding_a_unit_you_do_not_own_is_refused() {
+        let (game, user) = winter(1);
+        let res = BuildOrderCollector::new().submit_order(&game, user, vec![disband("GER", UnitType::Army, "par")]);
+        assert!(matches!(res, Err(OrderError::InvalidOrderPositions)));
+    }
+
+    #[test]
+    fn orders_for_another_nation_are_refused() {
+        let (game, user) = winter(1);
+        let res = BuildOrderCollector::new().submit_order(&game, user, vec![disband("FRA", UnitType::Army, "par")]);
+        assert!(matches!(res, Err(OrderError::InvalidOrderPositions)));
+    }
+}
@@ -1,6 +1,15 @@
 use crate::data::connection_pool::ConnectionPool;
+use crate::data::order_result::{self, Entity as OrderResultEntity};
+use crate::data::order_submission::{self, Entity as OrderSubmission};
 use std::sync::Arc;
 
+use common::protocol::OrderResult;
+use diplomacy::{Nation, ShortName, Time};
+use sea_orm::ActiveValue::{NotSet, Set};
+use sea_orm::{ColumnTrait, DbErr, EntityTrait, QueryFilter, QueryOrder};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
 pub struct OrderRepository {
     connection_pool:Arc<ConnectionPool>,
 }
@@ -12,4 +21,70 @@ impl OrderRepository {
             connection_pool: given_pool
         }
     }
-}
\ No newline at end of file
+
+    /// Archives a set of orders that a game accepted
+    pub async fn record_submission(&self, game_id: Uuid, user_id: Uuid, nation: &Nation, phase: &Time, orders: serde_json::Value) -> Result<(), DbErr> {
+        let submission = order_submission::ActiveModel {
+            submission_id: NotSet,
+            game_uuid: Set(game_id),
+            user_uuid: Set(user_id),
+            nation: Set(nation.short_name().into_owned()),
+            phase: Set(phase.short_name().into_owned()),
+            orders: Set(orders),
+            submitted_at: Set(OffsetDateTime::now_utc()),
+        };
+        OrderSubmission::insert(submission)
+            .exec(self.connection_pool.get_connection())
+            .await?;
+        Ok(())
+    }
+
+    /// Archives how every order fared once a phase was adjudicated
+    pub async fn record_results(&self, game_id: Uuid, phase: &Time, results: &[OrderResult]) -> Result<(), DbErr> {
+        if results.is_empty() {
+            return Ok(());
+        }
+        let resolved_at = OffsetDateTime::now_utc();
+        let rows = results.iter().map(|result| order_result::ActiveModel {
+            result_id: NotSet,
+            game_uuid: Set(game_id),
+            phase: Set(phase.short_name().into_owned()),
+            order_text: Set(result.order.clone()),
+            succeeded: Set(result.succeeded),
+            outcome: Set(result.outcome.clone()),
+            resolved_at: Set(resolved_at),
+        });
+        OrderResultEntity::insert_many(rows)
+            .exec(self.connection_pool.get_connection())
+            .await?;
+        Ok(())
+    }
+
+    /// Every order set handed in during a phase, oldest first
+    pub async fn submissions_for_phase(&self, game_id: Uuid, phase: &Time) -> Result<Vec<order_submission::Model>, DbErr> {
+        OrderSubmission::find()
+            .filter(order_submission::Column::GameUuid.eq(game_id))
+            .filter(order_submission::Column::Phase.eq(phase.short_name().into_owned()))
+            .order_by_asc(order_submission::Column::SubmittedAt)
+            .all(self.connection_pool.get_connection())
+            .await
+    }
+
+    /// The adjudicated results of a phase, empty if it has not been resolved
+    pub async fn results_for_phase(&self, game_id: Uuid, phase: &Time) -> Result<Vec<OrderResult>, DbErr> {
+        let results = OrderResultEntity::find()
+            .filter(order_result::Column::GameUuid.eq(game_id))
+            .filter(order_result::Column::Phase.eq(phase.short_name().into_owned()))
+            .order_by_asc(order_result::Column::OrderText)
+            .all(self.connection_pool.get_connection())
+            .await?;
+        Ok(results
+            .into_iter()
+            .map(|result| OrderResult {
+                order: result.order_text,
+                succeeded: result.succeeded,
+                outcome: result.outcome,
+            })
+            .collect())
+    }
+}
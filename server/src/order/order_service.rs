@@ -1,6 +1,13 @@
-use diplomacy::UnitPosition;
+use common::protocol::{GameEvent, NationOrders, PhaseHistory};
+use diplomacy::geo::RegionKey;
+use diplomacy::{Nation, Phase, Time, UnitType};
 use diplomacy::judge::{MappedBuildOrder, MappedMainOrder, MappedRetreatOrder};
+use sea_orm::DbErr;
+use serde::de::DeserializeOwned;
+use time::OffsetDateTime;
 use uuid::Uuid;
+use std::collections::BTreeMap;
+use std::fmt;
 use std::iter::Successors;
 use std::sync::Arc;
 
@@ -11,6 +18,7 @@ use crate::game::game_registry::GameRegistry;
 use crate::game::game_registry::GAME_REGISTRY;
 
 
+use crate::order::order_collector::get_order_positions;
 use crate::order::order_repository::OrderRepository;
 
 pub struct OrderService {
@@ -22,45 +30,184 @@ pub struct OrderService {
 }
 
 
+/// The event for a nation handing in orders
+fn submitted(game: Uuid, nation: Nation) -> GameEvent {
+    GameEvent::OrdersSubmitted { game, nation }
+}
+
 impl OrderService {
     pub fn new(given_repo: Arc<OrderRepository>) -> Self {
         Self {order_repo: given_repo}
     }
 
     pub async fn send_main_order(&self, session: &Session, orders: Vec<MappedMainOrder>) -> Result<OrderOutcome, OrderError> {
-        let mut registry = GAME_REGISTRY.write().await;
-        let game_id = session.current_game.unwrap();
-        let user_id = session.user;
-        let gh = registry
-            .get_mut_game(&game_id)
-            .ok_or(OrderError::GameNotFound)?;
-
-        let res = gh.receive_main_orders(user_id, orders)?;
-            Ok(res)
+        let raw = serde_json::to_value(&orders).unwrap_or_default();
+        self.send_with(session, Some(raw), submitted, |gh, user_id| gh.receive_main_orders(user_id, orders)).await
     }
 
     pub async fn send_retreat_order(&self, session: &Session, orders: Vec<MappedRetreatOrder>) -> Result<OrderOutcome, OrderError> {
+        let raw = serde_json::to_value(&orders).unwrap_or_default();
+        self.send_with(session, Some(raw), submitted, |gh, user_id| gh.receive_retreat_orders(user_id, orders)).await
+    }
+
+    pub async fn send_build_order(&self, session: &Session, orders: Vec<MappedBuildOrder>) -> Result<OrderOutcome, OrderError> {
+        let raw = serde_json::to_value(&orders).unwrap_or_default();
+        self.send_with(session, Some(raw), submitted, |gh, user_id| gh.receive_build_orders(user_id, orders)).await
+    }
+
+    pub async fn set_ready(&self, session: &Session, ready: bool) -> Result<OrderOutcome, OrderError> {
+        let action = move |game, nation| GameEvent::ReadinessChanged { game, nation, ready };
+        self.send_with(session, None, action, |gh, user_id| gh.set_ready(user_id, ready)).await
+    }
+
+    pub async fn set_draw_vote(&self, session: &Session, draw: bool) -> Result<OrderOutcome, OrderError> {
+        let action = move |game, nation| GameEvent::DrawVoteChanged { game, nation, draw };
+        self.send_with(session, None, action, |gh, user_id| gh.set_draw_vote(user_id, draw)).await
+    }
+
+    pub async fn units_without_orders(&self, session: &Session) -> Result<Vec<(UnitType, RegionKey)>, OrderError> {
+        let game_id = session.current_game.ok_or(OrderError::NotInGame)?;
+        let registry = GAME_REGISTRY.read().await;
+        registry
+            .get_game(&game_id)
+            .ok_or(OrderError::GameNotFound)?
+            .units_without_orders(&session.user)
+    }
+
+    /// Hands the request to the session's game, lets anyone watching know what happened
+    /// and archives any orders given along with any results
+    async fn send_with<A, F>(&self, session: &Session, raw: Option<serde_json::Value>, action: A, receive: F) -> Result<OrderOutcome, OrderError>
+    where
+        A: FnOnce(Uuid, Nation) -> GameEvent,
+        F: FnOnce(&mut GameHandler, Uuid) -> Result<OrderOutcome, OrderError>,
+    {
         let mut registry = GAME_REGISTRY.write().await;
-        let game_id = session.current_game.unwrap();
+        let game_id = session.current_game.ok_or(OrderError::NotInGame)?;
         let user_id = session.user;
         let gh = registry
             .get_mut_game(&game_id)
             .ok_or(OrderError::GameNotFound)?;
 
-        let res = gh.receive_retreat_orders(user_id, orders)?;
-            Ok(res)
+        let submitted_in = gh.instance.time().clone();
+        let res = receive(gh, user_id)?;
+        let events = gh.order_events(&user_id, submitted_in.clone(), action);
+        let nation = gh.instance.players.get(&user_id).cloned();
+        let results = if gh.has_resolved(&submitted_in) {
+            gh.last_results.clone()
+        } else {
+            Vec::new()
+        };
+
+        for event in events {
+            registry.publish(&game_id, event);
+        }
+        drop(registry);
+
+        // The orders already count in the game, losing the archive is not worth failing over
+        if let (Some(nation), Some(raw)) = (nation, raw) {
+            if let Err(e) = self.order_repo.record_submission(game_id, user_id, &nation, &submitted_in, raw).await {
+                eprintln!("[DB_ERROR] Failed to archive orders for game {game_id}: {e}");
+            }
+        }
+        if let Err(e) = self.order_repo.record_results(game_id, &submitted_in, &results).await {
+            eprintln!("[DB_ERROR] Failed to archive results for game {game_id}: {e}");
+        }
+        Ok(res)
     }
 
-    pub async fn send_build_order(&self, session: &Session, orders: Vec<MappedBuildOrder>) -> Result<OrderOutcome, OrderError> {
+    /// Resolves every game whose deadline has passed by `now` and returns the ones that moved on
+    pub async fn adjudicate_overdue(&self, now: OffsetDateTime) -> Vec<Uuid> {
         let mut registry = GAME_REGISTRY.write().await;
-        let game_id = session.current_game.unwrap();
-        let user_id = session.user;
-        let gh = registry
-            .get_mut_game(&game_id)
-            .ok_or(OrderError::GameNotFound)?;
+        let mut resolved = Vec::new();
+        for game_id in registry.overdue_games(now) {
+            let Some(gh) = registry.get_mut_game(&game_id) else {
+                continue;
+            };
+            let phase = gh.instance.time().clone();
+            let abandoned = match gh.resolve_overdue() {
+                Ok(abandoned) => abandoned,
+                Err(e) => {
+                    eprintln!("[GAME_ERROR] Failed to adjudicate game {game_id} after its deadline, trying again at the next one: {e:?}");
+                    continue;
+                }
+            };
+            let event = gh.resolved_event(phase.clone());
+            let results = gh.last_results.clone();
+            let finished = gh.finished_event();
+            registry.publish(&game_id, event);
+            for nation in abandoned {
+                registry.publish(&game_id, GameEvent::CivilDisorder { game: game_id, nation });
+            }
+            if let Some(event) = finished {
+                registry.publish(&game_id, event);
+            }
+            resolved.push((game_id, phase, results));
+        }
+        drop(registry);
+
+        for (game_id, phase, results) in &resolved {
+            if let Err(e) = self.order_repo.record_results(*game_id, phase, results).await {
+                eprintln!("[DB_ERROR] Failed to archive results for game {game_id}: {e}");
+            }
+        }
+        resolved.into_iter().map(|(game_id, ..)| game_id).collect()
+    }
+
+    /// The orders each nation ended up with in `phase` and how they were adjudicated. Nobody
+    /// gets to see the orders before the phase is resolved
+    pub async fn phase_history(&self, game_id: Uuid, phase: &Time) -> Result<PhaseHistory, DbErr> {
+        let pending = GAME_REGISTRY.read().await
+            .get_game(&game_id)
+            .is_some_and(|gh| gh.is_pending(phase));
+
+        let mut orders = Vec::new();
+        if !pending {
+            // Submissions come oldest first, keep every batch so they can be replayed in order
+            let mut batches: BTreeMap<String, Vec<serde_json::Value>> = BTreeMap::new();
+            for submission in self.order_repo.submissions_for_phase(game_id, phase).await? {
+                batches.entry(submission.nation).or_default().push(submission.orders);
+            }
+            orders = batches
+                .into_iter()
+                .map(|(nation, batches)| NationOrders {
+                    nation: Nation::from(nation.as_str()),
+                    orders: order_strings(phase.phase(), batches),
+                })
+                .collect();
+        }
 
-        let res = gh.receive_build_orders(user_id, orders)?;
-            Ok(res)
+        Ok(PhaseHistory {
+            game: game_id,
+            phase: phase.clone(),
+            orders,
+            results: self.order_repo.results_for_phase(game_id, phase).await?,
+        })
     }
 
 }
+
+/// Turns a nation's archived batches back into the usual `ENG: F lon -> nth` form. Main orders
+/// can be given a few units at a time, so like the collector each batch only replaces the units
+/// it names. Retreats and builds are always given in full, so only the last batch counts
+fn order_strings(phase: Phase, batches: Vec<serde_json::Value>) -> Vec<String> {
+    fn parse<O: DeserializeOwned>(batch: serde_json::Value) -> Vec<O> {
+        serde_json::from_value(batch).unwrap_or_default()
+    }
+    fn display<O: fmt::Display>(orders: &[O]) -> Vec<String> {
+        orders.iter().map(O::to_string).collect()
+    }
+
+    match phase {
+        Phase::Main => {
+            let mut orders: Vec<MappedMainOrder> = Vec::new();
+            for batch in batches.into_iter().map(parse::<MappedMainOrder>) {
+                let positions = get_order_positions(&batch);
+                orders.retain(|o| !positions.contains(&(o.unit_type, o.region.clone())));
+                orders.extend(batch);
+            }
+            display(&orders)
+        }
+        Phase::Retreat => display(&batches.into_iter().last().map(parse::<MappedRetreatOrder>).unwrap_or_default()),
+        Phase::Build => display(&batches.into_iter().last().map(parse::<MappedBuildOrder>).unwrap_or_default()),
+    }
+}